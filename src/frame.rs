@@ -0,0 +1,197 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::SerialPort;
+
+/// The size of the buffer each read from the port is made into
+const CHUNK_SIZE: usize = 256;
+
+/// Reads delimiter-terminated frames from a serial port
+///
+/// Each call to `read_until()` or `read_line()` waits up to the frame timeout for a complete
+/// frame. Unlike `io::BufReader`, data received before a timeout is kept rather than discarded,
+/// so the next call picks up the frame where the previous one left off. Any data received after
+/// the end of a frame is kept for the next call as well.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use serialport::FrameReader;
+///
+/// let port = serialport::new("/dev/ttyUSB0", 9600).open().unwrap();
+/// let mut reader = FrameReader::new(port);
+/// reader.set_frame_timeout(Some(Duration::from_secs(1)));
+///
+/// // Read a CR-terminated response of at most 64 bytes
+/// let response = reader.read_until(b"\r", 64).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FrameReader<P> {
+    port: P,
+    pending: Vec<u8>,
+    frame_timeout: Option<Duration>,
+    skip_lf: bool,
+}
+
+impl<P: SerialPort> FrameReader<P> {
+    /// Wraps a port, using its current read timeout as the frame timeout
+    pub fn new(port: P) -> FrameReader<P> {
+        FrameReader {
+            frame_timeout: port.read_timeout(),
+            port,
+            pending: Vec::new(),
+            skip_lf: false,
+        }
+    }
+
+    /// Returns the time allowed for receiving a whole frame
+    pub fn frame_timeout(&self) -> Option<Duration> {
+        self.frame_timeout
+    }
+
+    /// Sets the time allowed for receiving a whole frame
+    ///
+    /// A timeout of `None` waits indefinitely for a frame to complete.
+    pub fn set_frame_timeout(&mut self, timeout: Option<Duration>) {
+        self.frame_timeout = timeout;
+    }
+
+    /// Returns data that has been received but not yet returned as part of a frame
+    pub fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+
+    /// Gets a reference to the underlying port
+    pub fn get_ref(&self) -> &P {
+        &self.port
+    }
+
+    /// Gets a mutable reference to the underlying port
+    ///
+    /// Reading from the port directly bypasses any pending data.
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.port
+    }
+
+    /// Returns the underlying port, discarding any pending data
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// Reads a frame ending in any of the bytes in `delims`
+    ///
+    /// The returned frame includes its delimiter and is at most `max_len` bytes long.
+    ///
+    /// ## Errors
+    ///
+    /// * `TimedOut` if no complete frame arrived within the frame timeout. The partial frame is
+    ///   kept and completed by the next call.
+    /// * `InvalidData` if no delimiter was found within `max_len` bytes. These bytes are
+    ///   discarded so that reading can resume with the following data.
+    /// * `UnexpectedEof` if the port reached end of file.
+    /// * Any other error while reading from the port.
+    pub fn read_until(&mut self, delims: &[u8], max_len: usize) -> io::Result<Vec<u8>> {
+        let deadline = self.frame_timeout.map(|timeout| Instant::now() + timeout);
+        let mut scanned = 0;
+        loop {
+            let searchable = self.pending.len().min(max_len);
+            if let Some(pos) = self.pending[scanned..searchable]
+                .iter()
+                .position(|b| delims.contains(b))
+            {
+                let end = scanned + pos + 1;
+                return Ok(self.pending.drain(..end).collect());
+            }
+            if self.pending.len() >= max_len {
+                self.pending.drain(..max_len);
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "No delimiter found within the maximum frame length",
+                ));
+            }
+            scanned = self.pending.len();
+            self.fill(deadline)?;
+        }
+    }
+
+    /// Reads a line of text terminated by CR, LF or CRLF
+    ///
+    /// The returned line excludes the line ending and is at most `max_len` bytes long including
+    /// the line ending. Lines are expected to be UTF-8, which includes plain ASCII.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidData` if the line is not valid UTF-8. The line is discarded.
+    /// * Any error returned by `read_until()`.
+    pub fn read_line(&mut self, max_len: usize) -> io::Result<String> {
+        let deadline = self.frame_timeout.map(|timeout| Instant::now() + timeout);
+        // The LF of a CRLF line ending may not have arrived with the rest of the previous line
+        while self.skip_lf {
+            match self.pending.first() {
+                Some(&b'\n') => {
+                    self.pending.remove(0);
+                    self.skip_lf = false;
+                }
+                Some(_) => self.skip_lf = false,
+                None => self.fill(deadline)?,
+            }
+        }
+
+        let mut line = self.read_until(b"\r\n", max_len)?;
+        if line.pop() == Some(b'\r') {
+            self.skip_lf = true;
+        }
+        String::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reads once from the port into the pending data, waiting until at most `deadline`
+    fn fill(&mut self, deadline: Option<Instant>) -> io::Result<()> {
+        let timeout = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let read_timeout = self.port.read_timeout();
+        self.port.set_read_timeout(timeout)?;
+
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let result = loop {
+            match self.port.read(&mut chunk) {
+                Ok(0) => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "Reached end of file before the frame was complete",
+                    ))
+                }
+                Ok(n) => {
+                    self.pending.extend_from_slice(&chunk[..n]);
+                    break Ok(());
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => break Err(e),
+            }
+        };
+
+        self.port.set_read_timeout(read_timeout)?;
+        result
+    }
+}
+
+impl<P: SerialPort> io::Read for FrameReader<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            return self.port.read(buf);
+        }
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+impl<P: SerialPort> io::Write for FrameReader<P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
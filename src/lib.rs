@@ -15,6 +15,9 @@
 //! corresponding `TTYPort` and `COMPort` structs (that both implement the `SerialPort` trait).
 //! Using the platform-specific `open*()` functions will return the platform-specific port object
 //! which allows access to platform-specific functionality.
+//!
+//! On POSIX platforms, enabling the `tokio` feature adds an `AsyncTTYPort` which implements Tokio's
+//! `AsyncRead` and `AsyncWrite` traits and is opened with `SerialPortBuilder::open_native_async()`.
 
 #![deny(
     missing_docs,
@@ -30,18 +33,33 @@ use std::convert::From;
 use std::error::Error as StdError;
 use std::fmt;
 use std::io;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[cfg(unix)]
 mod posix;
+#[cfg(all(unix, feature = "tokio"))]
+pub use posix::AsyncTTYPort;
 #[cfg(unix)]
-pub use posix::{BreakDuration, TTYPort};
+pub use posix::{
+    BreakDuration, Canceller, LineError, LineErrorPolicy, MultidropByte, PortEvent, PortInterest,
+    PortSet, ReadMode, ReadTimestamp, ReceivedByte, SoftwareFlowControl, TTYPort,
+};
 
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
 pub use windows::COMPort;
 
+mod frame;
+mod notation;
+mod reader;
+mod split;
+mod xon_xoff;
+pub use frame::FrameReader;
+pub use reader::{ReadEvent, SerialReader};
+pub use split::{ReadHalf, SettingsHandle, WriteHalf};
+pub use xon_xoff::XonXoffPort;
+
 /// A type for results generated by interacting with serial ports
 ///
 /// The `Err` type is hard-wired to [`serialport::Error`](struct.Error.html).
@@ -161,6 +179,16 @@ pub enum Parity {
 
     /// Parity bit sets even number of 1 bits.
     Even,
+
+    /// Parity bit is always 1.
+    ///
+    /// This is only supported on Linux, Android and Windows.
+    Mark,
+
+    /// Parity bit is always 0.
+    ///
+    /// This is only supported on Linux, Android and Windows.
+    Space,
 }
 
 /// Number of stop bits
@@ -171,7 +199,14 @@ pub enum StopBits {
     /// One stop bit.
     One,
 
+    /// One and a half stop bits.
+    ///
+    /// Only valid with `DataBits::Five`.
+    OnePointFive,
+
     /// Two stop bits.
+    ///
+    /// Not valid with `DataBits::Five`.
     Two,
 }
 
@@ -201,6 +236,44 @@ pub enum ClearBuffer {
     All,
 }
 
+/// Specifies when [`apply_settings`] changes the settings of a port
+///
+/// [`apply_settings`]: trait.SerialPort.html#method.apply_settings
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ApplyWhen {
+    /// Change the settings immediately
+    Now,
+    /// Change the settings once all data written has been transmitted
+    Drain,
+    /// Change the settings once all data written has been transmitted, discarding data received
+    /// but not read
+    Flush,
+}
+
+/// A snapshot of the settings of a serial port
+///
+/// Read with [`settings`] and applied at once with [`apply_settings`].
+///
+/// [`settings`]: trait.SerialPort.html#method.settings
+/// [`apply_settings`]: trait.SerialPort.html#method.apply_settings
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SerialSettings {
+    /// The baud rate in symbols-per-second
+    pub baud_rate: u32,
+    /// Number of bits used to represent a character sent on the line
+    pub data_bits: DataBits,
+    /// The type of parity to use for error checking
+    pub parity: Parity,
+    /// Number of bits to use to signal the end of a character
+    pub stop_bits: StopBits,
+    /// The type of signalling to use for controlling data transfer
+    pub flow_control: FlowControl,
+    /// Amount of time to wait to receive data before timing out
+    pub read_timeout: Option<Duration>,
+    /// Amount of time to wait to transmit data before timing out
+    pub write_timeout: Option<Duration>,
+}
+
 /// A struct containing all serial port settings
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SerialPortBuilder {
@@ -217,7 +290,21 @@ pub struct SerialPortBuilder {
     /// Number of bits to use to signal the end of a character
     stop_bits: StopBits,
     /// Amount of time to wait to receive data before timing out
-    timeout: Duration,
+    read_timeout: Option<Duration>,
+    /// Amount of time to wait to transmit data before timing out
+    write_timeout: Option<Duration>,
+    /// Whether reads and writes fail immediately instead of waiting for the port to be ready
+    #[cfg(unix)]
+    nonblocking: bool,
+    /// When reads complete
+    #[cfg(unix)]
+    read_mode: ReadMode,
+    /// How bytes received with parity, framing or break errors are handled
+    #[cfg(unix)]
+    line_errors: Option<LineErrorPolicy>,
+    /// Software flow control characters and directions, applied on top of `flow_control`
+    #[cfg(unix)]
+    software_flow_control: Option<SoftwareFlowControl>,
 }
 
 impl SerialPortBuilder {
@@ -257,9 +344,66 @@ impl SerialPortBuilder {
         self
     }
 
-    /// Set the amount of time to wait to receive data before timing out
+    /// Set the amount of time to wait to receive or transmit data before timing out
+    ///
+    /// This sets both the read and the write timeout.
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
+        self.read_timeout = Some(timeout);
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the amount of time to wait to receive data before timing out
+    ///
+    /// A timeout of `None` waits indefinitely.
+    pub fn read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Set the amount of time to wait to transmit data before timing out
+    ///
+    /// A timeout of `None` waits indefinitely.
+    pub fn write_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// Set whether reads and writes should fail with `WouldBlock` instead of waiting
+    ///
+    /// See [`TTYPort::set_nonblocking`](struct.TTYPort.html#method.set_nonblocking).
+    #[cfg(unix)]
+    pub fn nonblocking(mut self, nonblocking: bool) -> Self {
+        self.nonblocking = nonblocking;
+        self
+    }
+
+    /// Set when reads complete
+    ///
+    /// See [`ReadMode`](enum.ReadMode.html).
+    #[cfg(unix)]
+    pub fn read_mode(mut self, read_mode: ReadMode) -> Self {
+        self.read_mode = read_mode;
+        self
+    }
+
+    /// Set how bytes received with parity, framing or break errors are handled
+    ///
+    /// See [`LineErrorPolicy`](enum.LineErrorPolicy.html). By default the platform's handling is
+    /// left unchanged.
+    #[cfg(unix)]
+    pub fn line_error_policy(mut self, policy: LineErrorPolicy) -> Self {
+        self.line_errors = Some(policy);
+        self
+    }
+
+    /// Set the software flow control characters and directions
+    ///
+    /// These settings are applied on top of `flow_control()`. See
+    /// [`SoftwareFlowControl`](struct.SoftwareFlowControl.html).
+    #[cfg(unix)]
+    pub fn software_flow_control(mut self, settings: SoftwareFlowControl) -> Self {
+        self.software_flow_control = Some(settings);
         self
     }
 
@@ -289,6 +433,15 @@ impl SerialPortBuilder {
     pub fn open_native(self) -> Result<COMPort> {
         windows::COMPort::open(&self)
     }
+
+    /// Open an asynchronous interface to the port with the specified settings
+    ///
+    /// The returned port implements Tokio's `AsyncRead` and `AsyncWrite` traits. This must be
+    /// called from within the context of a Tokio runtime.
+    #[cfg(all(unix, feature = "tokio"))]
+    pub fn open_native_async(self) -> Result<AsyncTTYPort> {
+        posix::AsyncTTYPort::open(&self)
+    }
 }
 
 /// A trait for serial port devices
@@ -342,8 +495,23 @@ pub trait SerialPort: Send + io::Read + io::Write {
     /// stop bits to a supported value.
     fn stop_bits(&self) -> Result<StopBits>;
 
-    /// Returns the current timeout.
-    fn timeout(&self) -> Duration;
+    /// Returns the current read timeout.
+    ///
+    /// If reads wait indefinitely, `Duration::MAX` is returned. Prefer `read_timeout()` and
+    /// `write_timeout()`, which can express this directly.
+    fn timeout(&self) -> Duration {
+        self.read_timeout().unwrap_or(Duration::MAX)
+    }
+
+    /// Returns the current read timeout.
+    ///
+    /// `None` indicates that reads wait indefinitely for data.
+    fn read_timeout(&self) -> Option<Duration>;
+
+    /// Returns the current write timeout.
+    ///
+    /// `None` indicates that writes wait indefinitely for the port to accept data.
+    fn write_timeout(&self) -> Option<Duration>;
 
     // Port settings setters
 
@@ -366,10 +534,80 @@ pub trait SerialPort: Send + io::Read + io::Write {
     fn set_parity(&mut self, parity: Parity) -> Result<()>;
 
     /// Sets the number of stop bits.
+    ///
+    /// `StopBits::OnePointFive` requires the data bits to be set to `DataBits::Five` first, while
+    /// `StopBits::Two` can't be used with `DataBits::Five`.
     fn set_stop_bits(&mut self, stop_bits: StopBits) -> Result<()>;
 
     /// Sets the timeout for future I/O operations.
-    fn set_timeout(&mut self, timeout: Duration) -> Result<()>;
+    ///
+    /// This sets both the read and the write timeout. A zero `Duration` causes operations to time
+    /// out immediately if the port is not ready.
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.set_read_timeout(Some(timeout))?;
+        self.set_write_timeout(Some(timeout))
+    }
+
+    /// Sets the timeout for future reads.
+    ///
+    /// A timeout of `None` causes reads to wait indefinitely for data.
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()>;
+
+    /// Sets the timeout for future writes.
+    ///
+    /// A timeout of `None` causes writes to wait indefinitely for the port to accept data.
+    fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()>;
+
+    /// Returns a snapshot of all settings of the port.
+    ///
+    /// ## Errors
+    ///
+    /// This function returns an error if any of the settings could not be determined. See the
+    /// individual getters for details.
+    fn settings(&self) -> Result<SerialSettings> {
+        Ok(SerialSettings {
+            baud_rate: self.baud_rate()?,
+            data_bits: self.data_bits()?,
+            parity: self.parity()?,
+            stop_bits: self.stop_bits()?,
+            flow_control: self.flow_control()?,
+            read_timeout: self.read_timeout(),
+            write_timeout: self.write_timeout(),
+        })
+    }
+
+    /// Changes all settings of the port at once.
+    ///
+    /// `TTYPort` and `COMPort` apply the line settings in a single call to the operating system,
+    /// so the port never passes through a mix of the old and new settings. Other ports fall back
+    /// to the individual setters.
+    ///
+    /// The timeouts are applied as well. Settings parsed from a string such as `115200-8N1` carry
+    /// the default timeouts of zero, so copy the port's current timeouts into them first to keep
+    /// those.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if the combination of settings is not supported.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    fn apply_settings(&mut self, settings: &SerialSettings, when: ApplyWhen) -> Result<()> {
+        match when {
+            ApplyWhen::Now => (),
+            ApplyWhen::Drain => self.flush()?,
+            ApplyWhen::Flush => {
+                self.flush()?;
+                self.clear(ClearBuffer::Input)?;
+            }
+        }
+        self.set_baud_rate(settings.baud_rate)?;
+        self.set_data_bits(settings.data_bits)?;
+        self.set_parity(settings.parity)?;
+        self.set_stop_bits(settings.stop_bits)?;
+        self.set_flow_control(settings.flow_control)?;
+        self.set_read_timeout(settings.read_timeout)?;
+        self.set_write_timeout(settings.write_timeout)
+    }
 
     // Functions for setting non-data control signal pins
 
@@ -499,11 +737,240 @@ pub trait SerialPort: Send + io::Read + io::Write {
     /// This function returns an error if the serial port couldn't be cloned.
     fn try_clone(&self) -> Result<Box<dyn SerialPort>>;
 
+    /// Splits the port into a reading half, a writing half and a handle for its settings.
+    ///
+    /// Unlike cloning the port with `try_clone()`, all three share a single port, so settings
+    /// changed through the `SettingsHandle` apply to both halves. The port is closed once all of
+    /// them have been dropped.
+    ///
+    /// # Errors
+    ///
+    /// * `Unknown` if the port does not support being split. Currently only `TTYPort` does.
+    fn split(self: Box<Self>) -> Result<(ReadHalf, WriteHalf, SettingsHandle)> {
+        Err(Error::new(
+            ErrorKind::Unknown,
+            "Splitting is not supported by this port",
+        ))
+    }
+
     /// Start transmitting a break
     fn set_break(&self) -> Result<()>;
 
     /// Stop transmitting a break
     fn clear_break(&self) -> Result<()>;
+
+    // Deadline-based I/O
+
+    /// Reads until `buf` is full or `deadline` passes.
+    ///
+    /// Unlike `io::Read::read_exact()`, where the read timeout applies to every underlying read,
+    /// the whole operation is bounded by a single `deadline`. The read timeout is adjusted while
+    /// reading and restored afterwards.
+    ///
+    /// Returns the number of bytes read into `buf`. This is less than `buf.len()` only if the
+    /// deadline passed, in which case the partially-received data is left at the start of `buf`.
+    ///
+    /// ## Errors
+    ///
+    /// This function returns an error if reading fails for any reason other than a timeout:
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    fn read_exact_timeout(&mut self, buf: &mut [u8], deadline: Instant) -> Result<usize> {
+        let read_timeout = self.read_timeout();
+        let mut count = 0;
+        let result = loop {
+            if count == buf.len() {
+                break Ok(count);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if let Err(e) = self.set_read_timeout(Some(remaining)) {
+                break Err(e);
+            }
+            match self.read(&mut buf[count..]) {
+                Ok(0) => {
+                    break Err(Error::new(
+                        ErrorKind::Io(io::ErrorKind::UnexpectedEof),
+                        "Reached end of file before the buffer was filled",
+                    ))
+                }
+                Ok(n) => count += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(ref e) if e.kind() == io::ErrorKind::TimedOut => break Ok(count),
+                Err(e) => break Err(e.into()),
+            }
+        };
+        let restored = self.set_read_timeout(read_timeout);
+        result.and_then(|count| restored.map(|_| count))
+    }
+
+    /// Writes all of `buf` unless `deadline` passes first.
+    ///
+    /// Unlike `io::Write::write_all()`, where the write timeout applies to every underlying write,
+    /// the whole operation is bounded by a single `deadline`. The write timeout is adjusted while
+    /// writing and restored afterwards.
+    ///
+    /// Returns the number of bytes written from the start of `buf`. This is less than `buf.len()`
+    /// only if the deadline passed.
+    ///
+    /// The deadline is checked whenever the port is waiting to accept more data. Depending on the
+    /// platform, a single write that the port has started accepting may run past the deadline.
+    ///
+    /// ## Errors
+    ///
+    /// This function returns an error if writing fails for any reason other than a timeout:
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    fn write_all_timeout(&mut self, buf: &[u8], deadline: Instant) -> Result<usize> {
+        let write_timeout = self.write_timeout();
+        let mut count = 0;
+        let result = loop {
+            if count == buf.len() {
+                break Ok(count);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if let Err(e) = self.set_write_timeout(Some(remaining)) {
+                break Err(e);
+            }
+            match self.write(&buf[count..]) {
+                Ok(0) => {
+                    break Err(Error::new(
+                        ErrorKind::Io(io::ErrorKind::WriteZero),
+                        "Failed to write the whole buffer",
+                    ))
+                }
+                Ok(n) => count += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(ref e) if e.kind() == io::ErrorKind::TimedOut => break Ok(count),
+                Err(e) => break Err(e.into()),
+            }
+        };
+        let restored = self.set_write_timeout(write_timeout);
+        result.and_then(|count| restored.map(|_| count))
+    }
+}
+
+impl<T: SerialPort + ?Sized> SerialPort for Box<T> {
+    fn name(&self) -> Option<String> {
+        (**self).name()
+    }
+
+    fn baud_rate(&self) -> Result<u32> {
+        (**self).baud_rate()
+    }
+
+    fn data_bits(&self) -> Result<DataBits> {
+        (**self).data_bits()
+    }
+
+    fn flow_control(&self) -> Result<FlowControl> {
+        (**self).flow_control()
+    }
+
+    fn parity(&self) -> Result<Parity> {
+        (**self).parity()
+    }
+
+    fn stop_bits(&self) -> Result<StopBits> {
+        (**self).stop_bits()
+    }
+
+    fn read_timeout(&self) -> Option<Duration> {
+        (**self).read_timeout()
+    }
+
+    fn write_timeout(&self) -> Option<Duration> {
+        (**self).write_timeout()
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<()> {
+        (**self).set_baud_rate(baud_rate)
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> Result<()> {
+        (**self).set_data_bits(data_bits)
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<()> {
+        (**self).set_flow_control(flow_control)
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> Result<()> {
+        (**self).set_parity(parity)
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> Result<()> {
+        (**self).set_stop_bits(stop_bits)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        (**self).set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        (**self).set_write_timeout(timeout)
+    }
+
+    fn settings(&self) -> Result<SerialSettings> {
+        (**self).settings()
+    }
+
+    fn apply_settings(&mut self, settings: &SerialSettings, when: ApplyWhen) -> Result<()> {
+        (**self).apply_settings(settings, when)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> Result<()> {
+        (**self).write_request_to_send(level)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> Result<()> {
+        (**self).write_data_terminal_ready(level)
+    }
+
+    fn read_clear_to_send(&mut self) -> Result<bool> {
+        (**self).read_clear_to_send()
+    }
+
+    fn read_data_set_ready(&mut self) -> Result<bool> {
+        (**self).read_data_set_ready()
+    }
+
+    fn read_ring_indicator(&mut self) -> Result<bool> {
+        (**self).read_ring_indicator()
+    }
+
+    fn read_carrier_detect(&mut self) -> Result<bool> {
+        (**self).read_carrier_detect()
+    }
+
+    fn bytes_to_read(&self) -> Result<u32> {
+        (**self).bytes_to_read()
+    }
+
+    fn bytes_to_write(&self) -> Result<u32> {
+        (**self).bytes_to_write()
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> Result<()> {
+        (**self).clear(buffer_to_clear)
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn SerialPort>> {
+        (**self).try_clone()
+    }
+
+    fn split(self: Box<Self>) -> Result<(ReadHalf, WriteHalf, SettingsHandle)> {
+        T::split(*self)
+    }
+
+    fn set_break(&self) -> Result<()> {
+        (**self).set_break()
+    }
+
+    fn clear_break(&self) -> Result<()> {
+        (**self).clear_break()
+    }
 }
 
 /// Contains all possible USB information about a `SerialPort`
@@ -561,7 +1028,16 @@ pub fn new<'a>(path: impl Into<std::borrow::Cow<'a, str>>, baud_rate: u32) -> Se
         flow_control: FlowControl::None,
         parity: Parity::None,
         stop_bits: StopBits::One,
-        timeout: Duration::from_millis(0),
+        read_timeout: Some(Duration::from_millis(0)),
+        write_timeout: Some(Duration::from_millis(0)),
+        #[cfg(unix)]
+        nonblocking: false,
+        #[cfg(unix)]
+        read_mode: ReadMode::Immediate,
+        #[cfg(unix)]
+        line_errors: None,
+        #[cfg(unix)]
+        software_flow_control: None,
     }
 }
 
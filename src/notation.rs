@@ -0,0 +1,338 @@
+//! The compact notation for port settings, such as `115200-8N1`
+//!
+//! Settings are written as the baud rate followed by the data bits, parity and stop bits, and
+//! optionally the flow control. Fields are separated by `-`, `,` or whitespace, and the data
+//! bits, parity and stop bits may also be written together, so `115200-8N1`, `115200 8N1` and
+//! `9600,7,E,2,hw` are all accepted.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{
+    DataBits, Error, ErrorKind, FlowControl, Parity, Result, SerialPortBuilder, SerialSettings,
+    StopBits,
+};
+
+fn invalid_input(description: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, description)
+}
+
+impl fmt::Display for DataBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = match *self {
+            DataBits::Five => "5",
+            DataBits::Six => "6",
+            DataBits::Seven => "7",
+            DataBits::Eight => "8",
+        };
+        f.write_str(bits)
+    }
+}
+
+impl FromStr for DataBits {
+    type Err = Error;
+
+    /// Parses the number of data bits, from `5` to `8`
+    fn from_str(s: &str) -> Result<DataBits> {
+        match s {
+            "5" => Ok(DataBits::Five),
+            "6" => Ok(DataBits::Six),
+            "7" => Ok(DataBits::Seven),
+            "8" => Ok(DataBits::Eight),
+            _ => Err(invalid_input(format!(
+                "Invalid data bits `{}`, expected 5, 6, 7 or 8",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Parity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parity = match *self {
+            Parity::None => "N",
+            Parity::Odd => "O",
+            Parity::Even => "E",
+            Parity::Mark => "M",
+            Parity::Space => "S",
+        };
+        f.write_str(parity)
+    }
+}
+
+impl FromStr for Parity {
+    type Err = Error;
+
+    /// Parses the parity from its initial or its name, ignoring case
+    fn from_str(s: &str) -> Result<Parity> {
+        match s.to_ascii_lowercase().as_str() {
+            "n" | "none" => Ok(Parity::None),
+            "o" | "odd" => Ok(Parity::Odd),
+            "e" | "even" => Ok(Parity::Even),
+            "m" | "mark" => Ok(Parity::Mark),
+            "s" | "space" => Ok(Parity::Space),
+            _ => Err(invalid_input(format!(
+                "Invalid parity `{}`, expected N, O, E, M or S",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StopBits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = match *self {
+            StopBits::One => "1",
+            StopBits::OnePointFive => "1.5",
+            StopBits::Two => "2",
+        };
+        f.write_str(bits)
+    }
+}
+
+impl FromStr for StopBits {
+    type Err = Error;
+
+    /// Parses the number of stop bits, `1`, `1.5` or `2`
+    fn from_str(s: &str) -> Result<StopBits> {
+        match s {
+            "1" => Ok(StopBits::One),
+            "1.5" => Ok(StopBits::OnePointFive),
+            "2" => Ok(StopBits::Two),
+            _ => Err(invalid_input(format!(
+                "Invalid stop bits `{}`, expected 1, 1.5 or 2",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FlowControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flow_control = match *self {
+            FlowControl::None => "none",
+            FlowControl::Software => "sw",
+            FlowControl::Hardware => "hw",
+        };
+        f.write_str(flow_control)
+    }
+}
+
+impl FromStr for FlowControl {
+    type Err = Error;
+
+    /// Parses the flow control, ignoring case
+    ///
+    /// Accepts `none`, `sw`, `software` or `xonxoff`, and `hw`, `hardware` or `rtscts`.
+    fn from_str(s: &str) -> Result<FlowControl> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(FlowControl::None),
+            "sw" | "software" | "xonxoff" => Ok(FlowControl::Software),
+            "hw" | "hardware" | "rtscts" => Ok(FlowControl::Hardware),
+            _ => Err(invalid_input(format!(
+                "Invalid flow control `{}`, expected none, sw or hw",
+                s
+            ))),
+        }
+    }
+}
+
+/// Formats the settings like `115200-8N1`, followed by the flow control unless it is `None`
+///
+/// The timeouts are not included.
+impl fmt::Display for SerialSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}{}{}",
+            self.baud_rate, self.data_bits, self.parity, self.stop_bits
+        )?;
+        if self.flow_control != FlowControl::None {
+            write!(f, "-{}", self.flow_control)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SerialSettings {
+    type Err = Error;
+
+    /// Parses settings such as `115200-8N1`, `115200 8N1` or `9600,7,E,2,hw`
+    ///
+    /// The flow control defaults to `FlowControl::None`. The notation doesn't include timeouts,
+    /// so both are set to the same default of zero as `serialport::new()`, where reads and writes
+    /// return as soon as they can instead of waiting. Set the timeouts before applying the parsed
+    /// settings to a port that should keep its own.
+    fn from_str(s: &str) -> Result<SerialSettings> {
+        let mut fields = s
+            .split(|c: char| c == '-' || c == ',' || c.is_whitespace())
+            .filter(|field| !field.is_empty());
+
+        let baud_rate = fields
+            .next()
+            .ok_or_else(|| invalid_input(format!("Missing baud rate in `{}`", s)))?;
+        let baud_rate = match baud_rate.parse() {
+            Ok(baud_rate) if baud_rate > 0 => baud_rate,
+            _ => return Err(invalid_input(format!("Invalid baud rate `{}`", baud_rate))),
+        };
+
+        let frame = fields.next().ok_or_else(|| {
+            invalid_input(format!(
+                "Missing data bits, parity and stop bits in `{}`",
+                s
+            ))
+        })?;
+        let (data_bits, parity, stop_bits): (DataBits, Parity, StopBits) = if frame.len() == 1 {
+            let mut next = |name: &str| {
+                fields
+                    .next()
+                    .ok_or_else(|| invalid_input(format!("Missing {} in `{}`", name, s)))
+            };
+            let parity = next("parity")?;
+            let stop_bits = next("stop bits")?;
+            (frame.parse()?, parity.parse()?, stop_bits.parse()?)
+        } else if frame.len() >= 3 && frame.is_ascii() {
+            (
+                frame[..1].parse()?,
+                frame[1..2].parse()?,
+                frame[2..].parse()?,
+            )
+        } else {
+            return Err(invalid_input(format!(
+                "Invalid frame format `{}`, expected data bits, parity and stop bits such as `8N1`",
+                frame
+            )));
+        };
+        // The same combinations are rejected when the settings are applied to a port
+        match (data_bits, stop_bits) {
+            (DataBits::Five, StopBits::Two) => {
+                return Err(invalid_input(format!(
+                    "2 stop bits can't be used with 5 data bits in `{}`",
+                    frame
+                )))
+            }
+            (DataBits::Five, StopBits::OnePointFive) => (),
+            (_, StopBits::OnePointFive) => {
+                return Err(invalid_input(format!(
+                    "1.5 stop bits require 5 data bits in `{}`",
+                    frame
+                )))
+            }
+            _ => (),
+        }
+
+        let flow_control = match fields.next() {
+            Some(flow_control) => flow_control.parse()?,
+            None => FlowControl::None,
+        };
+        if let Some(field) = fields.next() {
+            return Err(invalid_input(format!(
+                "Unexpected `{}` after the flow control in `{}`",
+                field, s
+            )));
+        }
+
+        Ok(SerialSettings {
+            baud_rate,
+            data_bits,
+            parity,
+            stop_bits,
+            flow_control,
+            read_timeout: Some(Duration::from_millis(0)),
+            write_timeout: Some(Duration::from_millis(0)),
+        })
+    }
+}
+
+impl SerialPortBuilder {
+    /// Creates a builder from a port name and its settings, such as `/dev/ttyUSB0@115200-8N1`
+    ///
+    /// The settings use the same notation as parsing `SerialSettings`. The timeouts are left at
+    /// their defaults.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if the specification couldn't be parsed.
+    pub fn from_spec(spec: &str) -> Result<SerialPortBuilder> {
+        let (path, settings) = spec.rsplit_once('@').ok_or_else(|| {
+            invalid_input(format!(
+                "Missing `@` between the port name and its settings in `{}`",
+                spec
+            ))
+        })?;
+        if path.is_empty() {
+            return Err(invalid_input(format!("Missing port name in `{}`", spec)));
+        }
+        let settings: SerialSettings = settings.parse()?;
+
+        Ok(crate::new(path, settings.baud_rate)
+            .data_bits(settings.data_bits)
+            .parity(settings.parity)
+            .stop_bits(settings.stop_bits)
+            .flow_control(settings.flow_control))
+    }
+}
+
+#[test]
+fn test_parse_settings() {
+    let settings: SerialSettings = "115200-8N1".parse().unwrap();
+    assert_eq!(settings.baud_rate, 115_200);
+    assert_eq!(settings.data_bits, DataBits::Eight);
+    assert_eq!(settings.parity, Parity::None);
+    assert_eq!(settings.stop_bits, StopBits::One);
+    assert_eq!(settings.flow_control, FlowControl::None);
+    assert_eq!(settings.read_timeout, Some(Duration::from_millis(0)));
+    assert_eq!(settings.write_timeout, Some(Duration::from_millis(0)));
+    assert_eq!(settings.to_string(), "115200-8N1");
+    assert_eq!("115200 8n1".parse::<SerialSettings>().unwrap(), settings);
+
+    let settings: SerialSettings = "9600,7,E,2,hw".parse().unwrap();
+    assert_eq!(settings.data_bits, DataBits::Seven);
+    assert_eq!(settings.parity, Parity::Even);
+    assert_eq!(settings.stop_bits, StopBits::Two);
+    assert_eq!(settings.flow_control, FlowControl::Hardware);
+    assert_eq!(settings.to_string(), "9600-7E2-hw");
+    assert_eq!(
+        settings.to_string().parse::<SerialSettings>().unwrap(),
+        settings
+    );
+
+    let settings: SerialSettings = "300-5N1.5".parse().unwrap();
+    assert_eq!(settings.stop_bits, StopBits::OnePointFive);
+
+    for invalid in &[
+        "",
+        "fast-8N1",
+        "0-8N1",
+        "9600",
+        "9600-9N1",
+        "9600-8X1",
+        "9600-8N3",
+        "9600-8N1.5",
+        "9600-5N2",
+        "9600-8N",
+        "9600,8,N",
+        "9600-8N1-rts",
+        "9600-8N1-hw-sw",
+    ] {
+        let err = invalid.parse::<SerialSettings>().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", invalid);
+    }
+}
+
+#[test]
+fn test_builder_from_spec() {
+    let builder = SerialPortBuilder::from_spec("/dev/ttyUSB0@115200-7O2-sw").unwrap();
+    assert_eq!(builder.path, "/dev/ttyUSB0");
+    assert_eq!(builder.baud_rate, 115_200);
+    assert_eq!(builder.data_bits, DataBits::Seven);
+    assert_eq!(builder.parity, Parity::Odd);
+    assert_eq!(builder.stop_bits, StopBits::Two);
+    assert_eq!(builder.flow_control, FlowControl::Software);
+
+    for invalid in &["/dev/ttyUSB0", "@9600-8N1", "/dev/ttyUSB0@9600"] {
+        let err = SerialPortBuilder::from_spec(invalid).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", invalid);
+    }
+}
@@ -0,0 +1,298 @@
+use std::io;
+use std::os::unix::prelude::*;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::unix::AsyncFd;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::{
+    ApplyWhen, ClearBuffer, DataBits, Error, FlowControl, Parity, Result, SerialPort,
+    SerialPortBuilder, SerialSettings, StopBits, TTYPort,
+};
+
+/// An asynchronous serial port for use with the Tokio runtime
+///
+/// This wraps a `TTYPort` whose file descriptor has been switched to non-blocking mode and
+/// registered with the Tokio reactor, so reads and writes through `AsyncRead` and `AsyncWrite`
+/// never park a thread. The port's timeouts do not apply to these operations; use
+/// `tokio::time::timeout()` instead.
+///
+/// All of the `SerialPort` settings remain available and operate on the underlying `TTYPort`.
+///
+/// This type is only available when the `tokio` feature is enabled.
+#[derive(Debug)]
+pub struct AsyncTTYPort {
+    inner: AsyncFd<TTYPort>,
+}
+
+impl AsyncTTYPort {
+    /// Opens a TTY device as an asynchronous serial port.
+    ///
+    /// This must be called from within the context of a Tokio runtime.
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device could not be opened. This could indicate that
+    ///   the device is already in use.
+    /// * `InvalidInput` if `path` is not a valid device name.
+    /// * `Io` for any other error while opening, initializing, or registering the device.
+    pub fn open(builder: &SerialPortBuilder) -> Result<AsyncTTYPort> {
+        TTYPort::open(builder).and_then(AsyncTTYPort::new)
+    }
+
+    /// Converts a blocking `TTYPort` into an asynchronous one.
+    ///
+    /// This must be called from within the context of a Tokio runtime.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the port could not be put into non-blocking mode or registered with the reactor.
+    pub fn new(mut port: TTYPort) -> Result<AsyncTTYPort> {
+        port.set_nonblocking(true)?;
+        // SAFETY: The `TTYPort` owns its file descriptor and only closes it when dropped, so the
+        // descriptor stays open and unchanged while the `AsyncFd` holds the port. The port is
+        // only handed out mutably through `get_mut()`, which is unsafe for this reason.
+        let inner = unsafe { AsyncFd::register(port) }.map_err(|e| e.into_parts().1)?;
+        Ok(AsyncTTYPort { inner })
+    }
+
+    /// Gets a reference to the underlying `TTYPort`.
+    pub fn get_ref(&self) -> &TTYPort {
+        self.inner.get_ref()
+    }
+
+    /// Gets a mutable reference to the underlying `TTYPort`.
+    ///
+    /// The returned port is in non-blocking mode, so reading from or writing to it directly will
+    /// fail with `WouldBlock` rather than waiting for the port to become ready. The settings are
+    /// also available through the `SerialPort` implementation of `AsyncTTYPort` itself.
+    ///
+    /// # Safety
+    ///
+    /// The port's file descriptor is registered with the Tokio reactor, so the port must not be
+    /// replaced, e.g. with `mem::swap()` or `mem::replace()`, through the returned reference.
+    pub unsafe fn get_mut(&mut self) -> &mut TTYPort {
+        self.port_mut()
+    }
+
+    fn port_mut(&mut self) -> &mut TTYPort {
+        self.inner.get_mut()
+    }
+
+    /// Deregisters the port from the Tokio reactor and returns the blocking `TTYPort`.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the port could not be returned to blocking mode.
+    pub fn into_inner(self) -> Result<TTYPort> {
+        let mut port = self.inner.into_inner();
+        port.set_nonblocking(false)?;
+        Ok(port)
+    }
+}
+
+impl AsRawFd for AsyncTTYPort {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsyncRead for AsyncTTYPort {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = match self.inner.poll_read_ready(cx) {
+                Poll::Ready(guard) => guard?,
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let unfilled = buf.initialize_unfilled();
+            let res = guard.try_io(|inner| {
+                nix::unistd::read(inner.as_raw_fd(), unfilled)
+                    .map_err(|e| io::Error::from(Error::from(e)))
+            });
+            match res {
+                Ok(Ok(n)) => {
+                    buf.advance(n);
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(Err(e)) => return Poll::Ready(Err(e)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncWrite for AsyncTTYPort {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = match self.inner.poll_write_ready(cx) {
+                Poll::Ready(guard) => guard?,
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let res = guard.try_io(|inner| {
+                nix::unistd::write(inner.as_raw_fd(), buf)
+                    .map_err(|e| io::Error::from(Error::from(e)))
+            });
+            match res {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    // Data is handed straight to the kernel on every write, so there is nothing buffered here
+    // to flush. Waiting for the kernel to transmit its output queue would require blocking in
+    // `tcdrain()`.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl io::Read for AsyncTTYPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.port_mut().read(buf)
+    }
+}
+
+impl io::Write for AsyncTTYPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.port_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port_mut().flush()
+    }
+}
+
+impl SerialPort for AsyncTTYPort {
+    fn name(&self) -> Option<String> {
+        self.get_ref().name()
+    }
+
+    fn baud_rate(&self) -> Result<u32> {
+        self.get_ref().baud_rate()
+    }
+
+    fn data_bits(&self) -> Result<DataBits> {
+        self.get_ref().data_bits()
+    }
+
+    fn flow_control(&self) -> Result<FlowControl> {
+        self.get_ref().flow_control()
+    }
+
+    fn parity(&self) -> Result<Parity> {
+        self.get_ref().parity()
+    }
+
+    fn stop_bits(&self) -> Result<StopBits> {
+        self.get_ref().stop_bits()
+    }
+
+    fn read_timeout(&self) -> Option<Duration> {
+        self.get_ref().read_timeout()
+    }
+
+    fn write_timeout(&self) -> Option<Duration> {
+        self.get_ref().write_timeout()
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> Result<()> {
+        self.port_mut().set_baud_rate(baud_rate)
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> Result<()> {
+        self.port_mut().set_data_bits(data_bits)
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> Result<()> {
+        self.port_mut().set_flow_control(flow_control)
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> Result<()> {
+        self.port_mut().set_parity(parity)
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> Result<()> {
+        self.port_mut().set_stop_bits(stop_bits)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.port_mut().set_read_timeout(timeout)
+    }
+
+    fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.port_mut().set_write_timeout(timeout)
+    }
+
+    fn settings(&self) -> Result<SerialSettings> {
+        self.get_ref().settings()
+    }
+
+    fn apply_settings(&mut self, settings: &SerialSettings, when: ApplyWhen) -> Result<()> {
+        self.port_mut().apply_settings(settings, when)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> Result<()> {
+        self.port_mut().write_request_to_send(level)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> Result<()> {
+        self.port_mut().write_data_terminal_ready(level)
+    }
+
+    fn read_clear_to_send(&mut self) -> Result<bool> {
+        self.port_mut().read_clear_to_send()
+    }
+
+    fn read_data_set_ready(&mut self) -> Result<bool> {
+        self.port_mut().read_data_set_ready()
+    }
+
+    fn read_ring_indicator(&mut self) -> Result<bool> {
+        self.port_mut().read_ring_indicator()
+    }
+
+    fn read_carrier_detect(&mut self) -> Result<bool> {
+        self.port_mut().read_carrier_detect()
+    }
+
+    fn bytes_to_read(&self) -> Result<u32> {
+        self.get_ref().bytes_to_read()
+    }
+
+    fn bytes_to_write(&self) -> Result<u32> {
+        self.get_ref().bytes_to_write()
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> Result<()> {
+        self.get_ref().clear(buffer_to_clear)
+    }
+
+    fn try_clone(&self) -> Result<Box<dyn SerialPort>> {
+        self.get_ref().try_clone()
+    }
+
+    fn set_break(&self) -> Result<()> {
+        self.get_ref().set_break()
+    }
+
+    fn clear_break(&self) -> Result<()> {
+        self.get_ref().clear_break()
+    }
+}
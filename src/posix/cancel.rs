@@ -0,0 +1,88 @@
+use std::os::unix::prelude::*;
+use std::sync::Arc;
+
+use nix::errno::Errno;
+use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+use nix::unistd;
+
+use crate::Result;
+
+/// A handle for cancelling blocking reads and writes on a `TTYPort`
+///
+/// Calling `cancel()` wakes every read or write that is waiting on a port sharing this handle,
+/// which then fails with an `io::ErrorKind::ConnectionAborted` error. Cancellation is sticky: later
+/// reads and writes fail the same way until `reset()` is called.
+///
+/// The handle is backed by a pipe that is polled alongside the port, so it can be cloned and used
+/// from any thread. Handles are obtained with `TTYPort::cancel_handle()`.
+#[derive(Clone, Debug)]
+pub struct Canceller {
+    pipe: Arc<CancelPipe>,
+}
+
+#[derive(Debug)]
+struct CancelPipe {
+    read_fd: RawFd,
+    write_fd: RawFd,
+}
+
+impl Drop for CancelPipe {
+    fn drop(&mut self) {
+        let _ = unistd::close(self.read_fd);
+        let _ = unistd::close(self.write_fd);
+    }
+}
+
+impl Canceller {
+    pub(crate) fn new() -> Result<Canceller> {
+        let (read_fd, write_fd) = unistd::pipe()?;
+        // Construct the pipe first so both ends are closed if configuring them fails
+        let pipe = CancelPipe { read_fd, write_fd };
+        for &fd in &[read_fd, write_fd] {
+            fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+            fcntl(fd, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+        }
+        Ok(Canceller {
+            pipe: Arc::new(pipe),
+        })
+    }
+
+    /// The file descriptor polled alongside the port, readable once cancelled
+    pub(crate) fn as_raw_fd(&self) -> RawFd {
+        self.pipe.read_fd
+    }
+
+    /// Cancels pending and future reads and writes until `reset()` is called
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the cancellation could not be signalled.
+    pub fn cancel(&self) -> Result<()> {
+        match unistd::write(self.pipe.write_fd, &[0]) {
+            // A full pipe means a cancellation is already pending
+            Ok(_) | Err(nix::Error::Sys(Errno::EAGAIN)) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Clears a previous cancellation so reads and writes wait normally again
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the pending cancellation could not be cleared.
+    pub fn reset(&self) -> Result<()> {
+        let mut buf = [0u8; 64];
+        loop {
+            match unistd::read(self.pipe.read_fd, &mut buf) {
+                Ok(0) | Err(nix::Error::Sys(Errno::EAGAIN)) => return Ok(()),
+                Ok(_) | Err(nix::Error::Sys(Errno::EINTR)) => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Returns whether a cancellation is pending
+    pub fn is_cancelled(&self) -> bool {
+        super::poll::is_readable(self.pipe.read_fd)
+    }
+}
@@ -44,6 +44,9 @@ mod raw {
     ))]
     ioctl_read!(tiocoutq, b't', 115, libc::c_int);
 
+    #[cfg(target_os = "linux")]
+    ioctl_read_bad!(tiocsergetlsr, libc::TIOCSERGETLSR, libc::c_int);
+
     ioctl_write_ptr_bad!(tiocmbic, libc::TIOCMBIC, libc::c_int);
     ioctl_write_ptr_bad!(tiocmbis, libc::TIOCMBIS, libc::c_int);
     ioctl_read!(
@@ -80,6 +83,40 @@ mod raw {
         0x2B,
         libc::termios2
     );
+    ioctl_write_ptr!(
+        #[cfg(any(
+            target_os = "android",
+            all(
+                target_os = "linux",
+                not(any(
+                    target_env = "musl",
+                    target_arch = "powerpc",
+                    target_arch = "powerpc64"
+                ))
+            )
+        ))]
+        tcsetsw2,
+        b'T',
+        0x2C,
+        libc::termios2
+    );
+    ioctl_write_ptr!(
+        #[cfg(any(
+            target_os = "android",
+            all(
+                target_os = "linux",
+                not(any(
+                    target_env = "musl",
+                    target_arch = "powerpc",
+                    target_arch = "powerpc64"
+                ))
+            )
+        ))]
+        tcsetsf2,
+        b'T',
+        0x2D,
+        libc::termios2
+    );
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     const IOSSIOSPEED: libc::c_ulong = 0x80045402;
     ioctl_write_ptr_bad!(
@@ -148,6 +185,24 @@ pub fn tiocoutq(fd: RawFd) -> Result<u32> {
         .map_err(|e| e.into())
 }
 
+/// Transmitter empty bit of the line status returned by `TIOCSERGETLSR`
+#[cfg(target_os = "linux")]
+const TIOCSER_TEMT: libc::c_int = 0x01;
+
+/// Returns whether the UART's transmitter is empty, i.e. the last byte has left the shift register
+///
+/// Returns `None` if the driver doesn't support the ioctl, as with ptys.
+#[cfg(target_os = "linux")]
+pub fn tiocsergetlsr(fd: RawFd) -> Result<Option<bool>> {
+    let mut retval: libc::c_int = 0;
+    match unsafe { raw::tiocsergetlsr(fd, &mut retval) } {
+        Ok(_) => Ok(Some(retval & TIOCSER_TEMT != 0)),
+        Err(nix::Error::Sys(nix::errno::Errno::ENOTTY))
+        | Err(nix::Error::Sys(nix::errno::Errno::EINVAL)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 pub fn tiocmbic(fd: RawFd, status: SerialLines) -> Result<()> {
     let bits = status.bits() as libc::c_int;
     unsafe { raw::tiocmbic(fd, &bits) }
@@ -198,6 +253,40 @@ pub fn tcsets2(fd: RawFd, options: &libc::termios2) -> Result<()> {
         .map_err(|e| e.into())
 }
 
+#[cfg(any(
+    target_os = "android",
+    all(
+        target_os = "linux",
+        not(any(
+            target_env = "musl",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        ))
+    )
+))]
+pub fn tcsetsw2(fd: RawFd, options: &libc::termios2) -> Result<()> {
+    unsafe { raw::tcsetsw2(fd, options) }
+        .map(|_| ())
+        .map_err(|e| e.into())
+}
+
+#[cfg(any(
+    target_os = "android",
+    all(
+        target_os = "linux",
+        not(any(
+            target_env = "musl",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        ))
+    )
+))]
+pub fn tcsetsf2(fd: RawFd, options: &libc::termios2) -> Result<()> {
+    unsafe { raw::tcsetsf2(fd, options) }
+        .map(|_| ())
+        .map_err(|e| e.into())
+}
+
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 pub fn iossiospeed(fd: RawFd, baud_rate: &libc::speed_t) -> Result<()> {
     unsafe { raw::iossiospeed(fd, baud_rate) }
@@ -1,9 +1,18 @@
+#[cfg(feature = "tokio")]
+pub use self::async_tty::*;
+pub use self::cancel::*;
 pub use self::enumerate::*;
+pub use self::port_set::*;
 pub use self::tty::*;
 
+#[cfg(feature = "tokio")]
+mod async_tty;
+mod cancel;
 mod enumerate;
 mod error;
 mod ioctl;
+mod parmrk;
 mod poll;
+mod port_set;
 mod termios;
 mod tty;
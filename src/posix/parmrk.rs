@@ -0,0 +1,70 @@
+//! Decoding of the input stream when parity errors are marked with `PARMRK`
+//!
+//! With `PARMRK` set and `IGNPAR` cleared, the kernel prefixes a byte received with a parity or
+//! framing error with `\377 \0`, and escapes a valid `\377` as `\377 \377`. A break is reported
+//! as `\377 \0 \0`, which can't be told apart from a `\0` received with an error.
+
+/// A byte from the input stream after removing the `PARMRK` marking
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MarkedByte {
+    /// A byte received without errors
+    Valid(u8),
+    /// A byte received with a parity or framing error, or a break if the value is 0
+    Error(u8),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum State {
+    #[default]
+    Normal,
+    Escape,
+    ErrorMark,
+}
+
+/// Removes the `PARMRK` marking from the input stream
+///
+/// Marking sequences may be split across reads, so the decoder keeps its state between calls.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ParmrkDecoder {
+    state: State,
+}
+
+impl ParmrkDecoder {
+    /// Feeds the next byte read from the port to the decoder
+    ///
+    /// Returns `None` while in the middle of a marking sequence.
+    pub(crate) fn push(&mut self, byte: u8) -> Option<MarkedByte> {
+        let (state, decoded) = match (self.state, byte) {
+            (State::Normal, 0xFF) => (State::Escape, None),
+            (State::Normal, b) => (State::Normal, Some(MarkedByte::Valid(b))),
+            (State::Escape, 0x00) => (State::ErrorMark, None),
+            // Only `\377` follows `\377` in a well-formed stream
+            (State::Escape, b) => (State::Normal, Some(MarkedByte::Valid(b))),
+            (State::ErrorMark, b) => (State::Normal, Some(MarkedByte::Error(b))),
+        };
+        self.state = state;
+        decoded
+    }
+}
+
+#[test]
+fn test_parmrk_decoder() {
+    let mut decoder = ParmrkDecoder::default();
+    let input = [0x41, 0xFF, 0xFF, 0xFF, 0x00, 0x42, 0xFF, 0x00, 0x00, 0x43];
+    let decoded: Vec<MarkedByte> = input.iter().filter_map(|&b| decoder.push(b)).collect();
+    assert_eq!(
+        decoded,
+        vec![
+            MarkedByte::Valid(0x41),
+            MarkedByte::Valid(0xFF),
+            MarkedByte::Error(0x42),
+            MarkedByte::Error(0x00),
+            MarkedByte::Valid(0x43),
+        ]
+    );
+
+    // Sequences split across reads are completed by the next byte
+    assert_eq!(decoder.push(0xFF), None);
+    assert_eq!(decoder.push(0x00), None);
+    assert_eq!(decoder.push(0x7F), Some(MarkedByte::Error(0x7F)));
+}
@@ -2,7 +2,6 @@
 
 use std::io;
 use std::os::unix::io::RawFd;
-use std::slice;
 use std::time::Duration;
 
 use nix::poll::{PollFd, PollFlags};
@@ -11,44 +10,105 @@ use nix::sys::signal::SigSet;
 #[cfg(target_os = "linux")]
 use nix::sys::time::{TimeSpec, TimeValLike};
 
-pub fn wait_read_fd(fd: RawFd, timeout: Duration) -> io::Result<()> {
-    wait_fd(fd, PollFlags::POLLIN, timeout)
+pub fn wait_read_fd(fd: RawFd, timeout: Option<Duration>, cancel: Option<RawFd>) -> io::Result<()> {
+    wait_fd(fd, PollFlags::POLLIN, timeout, cancel)
 }
 
-pub fn wait_write_fd(fd: RawFd, timeout: Duration) -> io::Result<()> {
-    wait_fd(fd, PollFlags::POLLOUT, timeout)
+pub fn wait_write_fd(
+    fd: RawFd,
+    timeout: Option<Duration>,
+    cancel: Option<RawFd>,
+) -> io::Result<()> {
+    wait_fd(fd, PollFlags::POLLOUT, timeout, cancel)
 }
 
-fn wait_fd(fd: RawFd, events: PollFlags, timeout: Duration) -> io::Result<()> {
-    use nix::errno::Errno::{EIO, EPIPE};
+/// Returns whether `fd` can be read from without blocking
+pub fn is_readable(fd: RawFd) -> bool {
+    let mut fds = [PollFd::new(fd, PollFlags::POLLIN)];
+    match nix::poll::poll(&mut fds, 0) {
+        Ok(1) => fds[0]
+            .revents()
+            .is_some_and(|e| e.contains(PollFlags::POLLIN)),
+        _ => false,
+    }
+}
 
-    let mut fd = PollFd::new(fd, events);
+/// The longest timeout in milliseconds that `ppoll()` is passed, which `TimeSpec` can represent
+/// on all targets
+#[cfg(target_os = "linux")]
+const MAX_TIMEOUT_MS: u64 = i32::MAX as u64 * 1000;
 
-    let milliseconds =
-        timeout.as_secs() as i64 * 1000 + i64::from(timeout.subsec_nanos()) / 1_000_000;
-    #[cfg(target_os = "linux")]
-    let wait_res = {
-        let timespec = TimeSpec::milliseconds(milliseconds);
-        nix::poll::ppoll(slice::from_mut(&mut fd), timespec, SigSet::empty())
-    };
-    #[cfg(not(target_os = "linux"))]
-    let wait_res = nix::poll::poll(slice::from_mut(&mut fd), milliseconds as nix::libc::c_int);
+/// The longest timeout in milliseconds that `poll()` is passed
+#[cfg(not(target_os = "linux"))]
+const MAX_TIMEOUT_MS: u64 = nix::libc::c_int::MAX as u64;
 
-    let wait = match wait_res {
-        Ok(r) => r,
-        Err(e) => return Err(io::Error::from(crate::Error::from(e))),
+/// Waits for any of `fds` to become ready, returning the number of ready entries
+///
+/// Zero ready entries means the timeout elapsed, while `None` waits indefinitely.
+pub fn wait_fds(fds: &mut [PollFd], timeout: Option<Duration>) -> io::Result<usize> {
+    let wait_res = match timeout {
+        Some(timeout) => {
+            // Longer timeouts, up to `Duration::MAX` as returned by `SerialPort::timeout()`, are
+            // clamped rather than overflowing
+            let milliseconds = timeout
+                .as_secs()
+                .checked_mul(1000)
+                .and_then(|ms| ms.checked_add(u64::from(timeout.subsec_millis())))
+                .map_or(MAX_TIMEOUT_MS, |ms| ms.min(MAX_TIMEOUT_MS));
+            #[cfg(target_os = "linux")]
+            let wait_res = {
+                let timespec = TimeSpec::milliseconds(milliseconds as i64);
+                nix::poll::ppoll(fds, timespec, SigSet::empty())
+            };
+            #[cfg(not(target_os = "linux"))]
+            let wait_res = nix::poll::poll(fds, milliseconds as nix::libc::c_int);
+            wait_res
+        }
+        // A negative timeout waits indefinitely
+        None => nix::poll::poll(fds, -1),
     };
+
+    match wait_res {
+        Ok(r) => Ok(r as usize),
+        Err(e) => Err(io::Error::from(crate::Error::from(e))),
+    }
+}
+
+/// Waits for `events` on `fd`, or until the optional `cancel` file descriptor becomes readable
+fn wait_fd(
+    fd: RawFd,
+    events: PollFlags,
+    timeout: Option<Duration>,
+    cancel: Option<RawFd>,
+) -> io::Result<()> {
+    use nix::errno::Errno::{EIO, EPIPE};
+
+    // poll() ignores negative file descriptors, so without a canceller only `fd` is waited on
+    let mut fds = [
+        PollFd::new(fd, events),
+        PollFd::new(cancel.unwrap_or(-1), PollFlags::POLLIN),
+    ];
+
+    let wait = wait_fds(&mut fds, timeout)?;
     // All errors generated by poll or ppoll are already caught by the nix wrapper around libc, so
     // here we only need to check if there's at least 1 event
-    if wait != 1 {
+    if wait == 0 {
         return Err(io::Error::new(
             io::ErrorKind::TimedOut,
             "Operation timed out",
         ));
     }
 
+    // A cancellation takes priority over the port being ready
+    if fds[1].revents().is_some_and(|e| !e.is_empty()) {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionAborted,
+            "Operation cancelled",
+        ));
+    }
+
     // Check the result of ppoll() by looking at the revents field
-    match fd.revents() {
+    match fds[0].revents() {
         Some(e) if e == events => return Ok(()),
         // If there was a hangout or invalid request
         Some(e) if e.contains(PollFlags::POLLHUP) || e.contains(PollFlags::POLLNVAL) => {
@@ -57,5 +117,5 @@ fn wait_fd(fd: RawFd, events: PollFlags, timeout: Duration) -> io::Result<()> {
         Some(_) | None => (),
     }
 
-    Err(io::Error::new(io::ErrorKind::Other, EIO.desc()))
+    Err(io::Error::other(EIO.desc()))
 }
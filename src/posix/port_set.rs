@@ -0,0 +1,179 @@
+use std::os::unix::prelude::*;
+use std::time::Duration;
+
+use nix::poll::{PollFd, PollFlags};
+
+use crate::Result;
+
+/// The readiness a port in a `PortSet` is waited on for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PortInterest {
+    /// Wait for the port to have data available to read
+    Readable,
+
+    /// Wait for the port to be able to accept more data
+    Writable,
+
+    /// Wait for the port to be either readable or writable
+    ReadWrite,
+}
+
+impl PortInterest {
+    fn poll_flags(self) -> PollFlags {
+        match self {
+            PortInterest::Readable => PollFlags::POLLIN,
+            PortInterest::Writable => PollFlags::POLLOUT,
+            PortInterest::ReadWrite => PollFlags::POLLIN | PollFlags::POLLOUT,
+        }
+    }
+}
+
+/// A port in a `PortSet` that became ready while waiting
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PortEvent {
+    /// The id returned by `PortSet::add()` when the port was added
+    pub id: usize,
+
+    /// The port has data available to read
+    pub readable: bool,
+
+    /// The port can accept more data
+    pub writable: bool,
+
+    /// The port was hung up or is in an error state, e.g. because the device was disconnected.
+    /// Reading from or writing to it will report the error.
+    pub error: bool,
+}
+
+/// A set of ports that can be waited on together
+///
+/// Ports are added with the readiness they should be waited on for and identified by the id
+/// returned from `add()`. `wait()` then blocks the calling thread until any of them is ready,
+/// without having to spin through each port individually.
+///
+/// The set only tracks the ports' file descriptors, so ports can still be read from and written to
+/// while they are in the set. A port should be removed from the set before it is dropped.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::io::Read;
+/// use std::time::Duration;
+/// use serialport::{PortInterest, PortSet, TTYPort};
+///
+/// let (mut port1, mut port2) = TTYPort::pair().unwrap();
+/// let mut set = PortSet::new();
+/// let id1 = set.add(&port1, PortInterest::Readable);
+/// let id2 = set.add(&port2, PortInterest::Readable);
+///
+/// let mut buf = [0u8; 64];
+/// for event in set.wait(Some(Duration::from_secs(1))).unwrap() {
+///     if event.id == id1 {
+///         port1.read(&mut buf).unwrap();
+///     } else if event.id == id2 {
+///         port2.read(&mut buf).unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PortSet {
+    ports: Vec<PortEntry>,
+    next_id: usize,
+}
+
+#[derive(Debug)]
+struct PortEntry {
+    id: usize,
+    fd: RawFd,
+    interest: PortInterest,
+}
+
+impl PortSet {
+    /// Creates an empty set
+    pub fn new() -> PortSet {
+        PortSet::default()
+    }
+
+    /// Adds a port to the set, returning the id identifying it in events
+    ///
+    /// Any type exposing a file descriptor can be added, e.g. `TTYPort`.
+    pub fn add<P: AsRawFd>(&mut self, port: &P, interest: PortInterest) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.ports.push(PortEntry {
+            id,
+            fd: port.as_raw_fd(),
+            interest,
+        });
+        id
+    }
+
+    /// Changes the readiness the port with the given id is waited on for
+    ///
+    /// Returns `false` if no port with that id is in the set.
+    pub fn set_interest(&mut self, id: usize, interest: PortInterest) -> bool {
+        match self.ports.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.interest = interest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the port with the given id from the set
+    ///
+    /// Returns `false` if no port with that id is in the set.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let len = self.ports.len();
+        self.ports.retain(|entry| entry.id != id);
+        self.ports.len() != len
+    }
+
+    /// Returns the number of ports in the set
+    pub fn len(&self) -> usize {
+        self.ports.len()
+    }
+
+    /// Returns whether the set contains no ports
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Waits until at least one port in the set is ready or the timeout elapses
+    ///
+    /// Returns an event for every port that is ready, in the order they were added. The returned
+    /// list is empty if the timeout elapsed first. A timeout of `None` waits indefinitely.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if waiting on the ports failed.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<Vec<PortEvent>> {
+        let mut fds: Vec<PollFd> = self
+            .ports
+            .iter()
+            .map(|entry| PollFd::new(entry.fd, entry.interest.poll_flags()))
+            .collect();
+
+        if super::poll::wait_fds(&mut fds, timeout)? == 0 {
+            return Ok(Vec::new());
+        }
+
+        let errors = PollFlags::POLLERR | PollFlags::POLLHUP | PollFlags::POLLNVAL;
+        let events = self
+            .ports
+            .iter()
+            .zip(fds.iter())
+            .filter_map(|(entry, fd)| match fd.revents() {
+                Some(revents) if !revents.is_empty() => Some(PortEvent {
+                    id: entry.id,
+                    readable: revents.contains(PollFlags::POLLIN),
+                    writable: revents.contains(PollFlags::POLLOUT),
+                    error: revents.intersects(errors),
+                }),
+                _ => None,
+            })
+            .collect();
+        Ok(events)
+    }
+}
@@ -1,14 +1,17 @@
 // A set of helper functions for working with the `termios` and `termios2` structs
 use cfg_if::cfg_if;
 
-use crate::{DataBits, FlowControl, Parity, Result, StopBits};
+use crate::{
+    ApplyWhen, DataBits, Error, ErrorKind, FlowControl, LineErrorPolicy, Parity, Result,
+    SoftwareFlowControl, StopBits,
+};
 use nix::libc;
 
 use std::os::unix::prelude::*;
 
 cfg_if! {
     if #[cfg(any(
-        target_os = "dragonflybsd",
+        target_os = "dragonfly",
         target_os = "freebsd",
         target_os = "ios",
         target_os = "macos",
@@ -59,7 +62,7 @@ pub(crate) fn get_termios(fd: RawFd) -> Result<Termios> {
 }
 
 #[cfg(any(
-    target_os = "dragonflybsd",
+    target_os = "dragonfly",
     target_os = "freebsd",
     target_os = "netbsd",
     target_os = "openbsd",
@@ -93,19 +96,29 @@ pub(crate) fn get_termios(fd: RawFd) -> Result<Termios> {
     )
 ))]
 pub(crate) fn get_termios(fd: RawFd) -> Result<Termios> {
-    crate::posix::ioctl::tcgets2(fd).into()
+    crate::posix::ioctl::tcgets2(fd)
 }
 
 #[cfg(any(target_os = "ios", target_os = "macos",))]
 pub(crate) fn set_termios(fd: RawFd, termios: &libc::termios, baud_rate: u32) -> Result<()> {
-    let res = unsafe { libc::tcsetattr(fd, libc::TCSANOW, termios) };
+    set_termios_when(fd, termios, baud_rate, ApplyWhen::Now)
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos",))]
+pub(crate) fn set_termios_when(
+    fd: RawFd,
+    termios: &libc::termios,
+    baud_rate: u32,
+    when: ApplyWhen,
+) -> Result<()> {
+    let res = unsafe { libc::tcsetattr(fd, tcsetattr_action(when), termios) };
     nix::errno::Errno::result(res)?;
     crate::posix::ioctl::iossiospeed(fd, &(baud_rate as libc::speed_t))?;
     Ok(())
 }
 
 #[cfg(any(
-    target_os = "dragonflybsd",
+    target_os = "dragonfly",
     target_os = "freebsd",
     target_os = "netbsd",
     target_os = "openbsd",
@@ -119,11 +132,53 @@ pub(crate) fn set_termios(fd: RawFd, termios: &libc::termios, baud_rate: u32) ->
     )
 ))]
 pub(crate) fn set_termios(fd: RawFd, termios: &libc::termios) -> Result<()> {
-    let res = unsafe { libc::tcsetattr(fd, libc::TCSANOW, termios) };
+    set_termios_when(fd, termios, ApplyWhen::Now)
+}
+
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    all(
+        target_os = "linux",
+        any(
+            target_env = "musl",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        )
+    )
+))]
+pub(crate) fn set_termios_when(fd: RawFd, termios: &libc::termios, when: ApplyWhen) -> Result<()> {
+    let res = unsafe { libc::tcsetattr(fd, tcsetattr_action(when), termios) };
     nix::errno::Errno::result(res)?;
     Ok(())
 }
 
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    all(
+        target_os = "linux",
+        any(
+            target_env = "musl",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        )
+    )
+))]
+fn tcsetattr_action(when: ApplyWhen) -> libc::c_int {
+    match when {
+        ApplyWhen::Now => libc::TCSANOW,
+        ApplyWhen::Drain => libc::TCSADRAIN,
+        ApplyWhen::Flush => libc::TCSAFLUSH,
+    }
+}
+
 #[cfg(any(
     target_os = "android",
     all(
@@ -136,10 +191,34 @@ pub(crate) fn set_termios(fd: RawFd, termios: &libc::termios) -> Result<()> {
     )
 ))]
 pub(crate) fn set_termios(fd: RawFd, termios: &Termios) -> Result<()> {
-    crate::posix::ioctl::tcsets2(fd, &termios)
+    set_termios_when(fd, termios, ApplyWhen::Now)
+}
+
+#[cfg(any(
+    target_os = "android",
+    all(
+        target_os = "linux",
+        not(any(
+            target_env = "musl",
+            target_arch = "powerpc",
+            target_arch = "powerpc64"
+        ))
+    )
+))]
+pub(crate) fn set_termios_when(fd: RawFd, termios: &Termios, when: ApplyWhen) -> Result<()> {
+    match when {
+        ApplyWhen::Now => crate::posix::ioctl::tcsets2(fd, termios),
+        ApplyWhen::Drain => crate::posix::ioctl::tcsetsw2(fd, termios),
+        ApplyWhen::Flush => crate::posix::ioctl::tcsetsf2(fd, termios),
+    }
 }
 
-pub(crate) fn set_parity(termios: &mut Termios, parity: Parity) {
+pub(crate) fn set_parity(termios: &mut Termios, parity: Parity) -> Result<()> {
+    // Mark and space parity reuse `PARODD` to select the value of the parity bit
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        termios.c_cflag &= !libc::CMSPAR;
+    }
     match parity {
         Parity::None => {
             termios.c_cflag &= !(libc::PARENB | libc::PARODD);
@@ -157,7 +236,62 @@ pub(crate) fn set_parity(termios: &mut Termios, parity: Parity) {
             termios.c_iflag |= libc::INPCK;
             termios.c_iflag &= !libc::IGNPAR;
         }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        Parity::Mark => {
+            termios.c_cflag |= libc::PARENB | libc::CMSPAR | libc::PARODD;
+            termios.c_iflag |= libc::INPCK;
+            termios.c_iflag &= !libc::IGNPAR;
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        Parity::Space => {
+            termios.c_cflag &= !libc::PARODD;
+            termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+            termios.c_iflag |= libc::INPCK;
+            termios.c_iflag &= !libc::IGNPAR;
+        }
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        Parity::Mark | Parity::Space => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Mark and space parity are not supported on this platform",
+            ));
+        }
     };
+    Ok(())
+}
+
+/// Enables or disables marking bytes received with parity or framing errors using `PARMRK`
+pub(crate) fn set_parity_marking(termios: &mut Termios, enabled: bool) {
+    if enabled {
+        termios.c_iflag |= libc::PARMRK;
+        termios.c_iflag &= !(libc::IGNPAR | libc::ISTRIP);
+    } else {
+        termios.c_iflag &= !libc::PARMRK;
+    }
+}
+
+pub(crate) fn set_line_error_policy(termios: &mut Termios, policy: Option<LineErrorPolicy>) {
+    match policy {
+        None => {
+            set_parity_marking(termios, false);
+            termios.c_iflag &= !libc::IGNBRK;
+            // Matches the handling chosen by `set_parity()`
+            if termios.c_cflag & libc::PARENB == 0 {
+                termios.c_iflag |= libc::IGNPAR;
+            } else {
+                termios.c_iflag &= !libc::IGNPAR;
+            }
+        }
+        Some(LineErrorPolicy::Ignore) => {
+            set_parity_marking(termios, false);
+            termios.c_iflag |= libc::IGNPAR | libc::IGNBRK;
+        }
+        Some(LineErrorPolicy::Replace(_)) | Some(LineErrorPolicy::Report) => {
+            set_parity_marking(termios, true);
+            // Breaks are marked like other errors instead of raising `SIGINT`
+            termios.c_iflag &= !(libc::IGNBRK | libc::BRKINT);
+        }
+    }
 }
 
 pub(crate) fn set_flow_control(termios: &mut Termios, flow_control: FlowControl) {
@@ -177,6 +311,27 @@ pub(crate) fn set_flow_control(termios: &mut Termios, flow_control: FlowControl)
     };
 }
 
+pub(crate) fn set_software_flow_control(termios: &mut Termios, settings: SoftwareFlowControl) {
+    termios.c_cc[libc::VSTART] = settings.xon;
+    termios.c_cc[libc::VSTOP] = settings.xoff;
+    for &(flag, enabled) in &[
+        (libc::IXON, settings.output),
+        (libc::IXOFF, settings.input),
+        (libc::IXANY, settings.xany),
+    ] {
+        if enabled {
+            termios.c_iflag |= flag;
+        } else {
+            termios.c_iflag &= !flag;
+        }
+    }
+}
+
+pub(crate) fn set_vmin_vtime(termios: &mut Termios, vmin: u8, vtime: u8) {
+    termios.c_cc[libc::VMIN] = vmin;
+    termios.c_cc[libc::VTIME] = vtime;
+}
+
 pub(crate) fn set_data_bits(termios: &mut Termios, data_bits: DataBits) {
     let size = match data_bits {
         DataBits::Five => libc::CS5,
@@ -189,11 +344,28 @@ pub(crate) fn set_data_bits(termios: &mut Termios, data_bits: DataBits) {
     termios.c_cflag |= size;
 }
 
-pub(crate) fn set_stop_bits(termios: &mut Termios, stop_bits: StopBits) {
+pub(crate) fn set_stop_bits(termios: &mut Termios, stop_bits: StopBits) -> Result<()> {
     match stop_bits {
         StopBits::One => termios.c_cflag &= !libc::CSTOPB,
+        // `CSTOPB` selects 1.5 stop bits instead of 2 when combined with `CS5`
+        StopBits::Two if termios.c_cflag & libc::CSIZE == libc::CS5 => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "2 stop bits can't be used with 5 data bits",
+            ))
+        }
         StopBits::Two => termios.c_cflag |= libc::CSTOPB,
+        StopBits::OnePointFive if termios.c_cflag & libc::CSIZE == libc::CS5 => {
+            termios.c_cflag |= libc::CSTOPB
+        }
+        StopBits::OnePointFive => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "1.5 stop bits require 5 data bits",
+            ))
+        }
     };
+    Ok(())
 }
 
 #[cfg(any(
@@ -216,7 +388,7 @@ pub(crate) fn set_baud_rate(termios: &mut Termios, baud_rate: u32) {
 
 // BSDs use the baud rate as the constant value so there's no translation necessary
 #[cfg(any(
-    target_os = "dragonflybsd",
+    target_os = "dragonfly",
     target_os = "freebsd",
     target_os = "netbsd",
     target_os = "openbsd"
@@ -280,3 +452,56 @@ pub(crate) fn set_baud_rate(termios: &mut Termios, baud_rate: u32) {
     let res = unsafe { libc::cfsetspeed(termios, baud_rate) };
     nix::errno::Errno::result(res).expect("cfsetspeed failed");
 }
+
+#[test]
+fn test_set_parity_mark_space() {
+    let mut termios: Termios = unsafe { std::mem::zeroed() };
+
+    let result = set_parity(&mut termios, Parity::Mark);
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        result.expect("Mark parity should be supported");
+        let flags = libc::PARENB | libc::CMSPAR | libc::PARODD;
+        assert_eq!(termios.c_cflag & flags, flags);
+
+        set_parity(&mut termios, Parity::Space).unwrap();
+        assert_eq!(
+            termios.c_cflag & (libc::PARENB | libc::CMSPAR | libc::PARODD),
+            libc::PARENB | libc::CMSPAR
+        );
+
+        // Other parities clear `CMSPAR` again
+        set_parity(&mut termios, Parity::Odd).unwrap();
+        assert_eq!(termios.c_cflag & libc::CMSPAR, 0);
+    }
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert_eq!(result.unwrap_err().kind(), crate::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_set_stop_bits_one_point_five() {
+    let mut termios: Termios = unsafe { std::mem::zeroed() };
+
+    set_data_bits(&mut termios, DataBits::Eight);
+    let err = set_stop_bits(&mut termios, StopBits::OnePointFive).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(termios.c_cflag & libc::CSTOPB, 0);
+
+    set_data_bits(&mut termios, DataBits::Five);
+    set_stop_bits(&mut termios, StopBits::OnePointFive).unwrap();
+    assert_eq!(termios.c_cflag & libc::CSTOPB, libc::CSTOPB);
+}
+
+#[test]
+fn test_set_stop_bits_two() {
+    let mut termios: Termios = unsafe { std::mem::zeroed() };
+
+    set_data_bits(&mut termios, DataBits::Five);
+    let err = set_stop_bits(&mut termios, StopBits::Two).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(termios.c_cflag & libc::CSTOPB, 0);
+
+    set_data_bits(&mut termios, DataBits::Eight);
+    set_stop_bits(&mut termios, StopBits::Two).unwrap();
+    assert_eq!(termios.c_cflag & libc::CSTOPB, libc::CSTOPB);
+}
@@ -1,19 +1,31 @@
+use std::collections::VecDeque;
 use std::mem::MaybeUninit;
 use std::os::unix::prelude::*;
 use std::path::Path;
-use std::time::Duration;
-use std::{io, mem};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+use std::{io, mem, thread};
 
 use nix::fcntl::{fcntl, OFlag};
+use nix::sys::termios::FlowArg;
 use nix::{self, libc, unistd};
 
 use crate::posix::ioctl::{self, SerialLines};
+use crate::posix::parmrk::{MarkedByte, ParmrkDecoder};
 use crate::posix::termios;
+use crate::posix::Canceller;
 use crate::{
-    ClearBuffer, DataBits, Error, ErrorKind, FlowControl, Parity, Result, SerialPort,
-    SerialPortBuilder, StopBits,
+    ApplyWhen, ClearBuffer, DataBits, Error, ErrorKind, FlowControl, Parity, ReadHalf, Result,
+    SerialPort, SerialPortBuilder, SerialSettings, SettingsHandle, StopBits, WriteHalf,
 };
 
+/// How often `TTYPort::flush_timeout()` checks whether the output has been transmitted
+const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The most buffers passed to a single `readv()`/`writev()` call, matching `IOV_MAX` on the
+/// supported platforms. Any further buffers are left for the next call.
+const IOV_MAX: usize = 1024;
+
 /// Convenience method for removing exclusive access from
 /// a fd and closing it.
 fn close(fd: RawFd) {
@@ -40,8 +52,17 @@ fn close(fd: RawFd) {
 #[derive(Debug)]
 pub struct TTYPort {
     fd: RawFd,
-    timeout: Duration,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
     exclusive: bool,
+    nonblocking: bool,
+    read_mode: ReadMode,
+    canceller: Option<Canceller>,
+    line_errors: Option<LineErrorPolicy>,
+    output_suspended: bool,
+    parmrk: ParmrkDecoder,
+    multidrop_pending: VecDeque<MultidropByte>,
+    tx_pending: Mutex<Option<Instant>>,
     port_name: Option<String>,
     #[cfg(any(target_os = "ios", target_os = "macos"))]
     baud_rate: u32,
@@ -56,6 +77,174 @@ pub enum BreakDuration {
     Arbitrary(std::num::NonZeroI32),
 }
 
+/// The time at which data read with `TTYPort::read_timestamped()` arrived
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadTimestamp {
+    /// Monotonic time, suitable for measuring the gaps between received chunks
+    pub instant: Instant,
+    /// Wall-clock time, suitable for correlating with other logs
+    pub system_time: SystemTime,
+}
+
+/// A byte received in 9-bit multi-drop mode, see `TTYPort::read_multidrop()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultidropByte {
+    /// A byte received with the 9th bit set
+    Address(u8),
+    /// A byte received with the 9th bit cleared
+    Data(u8),
+}
+
+impl MultidropByte {
+    /// Classifies a byte received while checking for mark parity if `mark`, or space parity
+    /// otherwise
+    fn classify(byte: MarkedByte, mark: bool) -> MultidropByte {
+        // A parity error means the 9th bit doesn't match the parity being checked
+        match byte {
+            MarkedByte::Valid(b) if mark => MultidropByte::Address(b),
+            MarkedByte::Error(b) if !mark => MultidropByte::Address(b),
+            MarkedByte::Valid(b) | MarkedByte::Error(b) => MultidropByte::Data(b),
+        }
+    }
+}
+
+/// An error detected on the line while receiving a byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineError {
+    /// The byte had the wrong parity or was missing its stop bit
+    ParityOrFraming,
+    /// A break condition was detected
+    ///
+    /// A NUL byte received with a parity or framing error is reported as a break as well, as the
+    /// two can't be told apart.
+    Break,
+}
+
+/// A byte read with `TTYPort::read_with_errors()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReceivedByte {
+    /// The received byte, or the substitute byte for `LineErrorPolicy::Replace`
+    pub value: u8,
+    /// The error the byte was received with, if any
+    pub error: Option<LineError>,
+}
+
+/// Specifies how bytes received with parity, framing or break errors are handled
+///
+/// Parity errors are only detected when parity is enabled on the port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineErrorPolicy {
+    /// Discard bytes received with errors, as well as breaks
+    Ignore,
+    /// Replace bytes received with errors by the given byte in `read_with_errors()`
+    ///
+    /// The error is still reported alongside the substitute byte. Like with `Report`, the data
+    /// returned by `read()` includes the escape sequences that mark errors.
+    Replace(u8),
+    /// Report bytes received with errors through `read_with_errors()`
+    ///
+    /// This enables the `PARMRK` termios setting. The data returned by `read()` includes the
+    /// escape sequences that mark errors: `\377 \0` before a byte received with an error, and
+    /// `\377 \377` for a valid `\377` byte.
+    Report,
+}
+
+/// Software (XON/XOFF) flow control settings
+///
+/// Unlike `FlowControl::Software`, this allows choosing the flow control characters and enabling
+/// flow control for each direction separately. The default uses the standard DC1/DC3 characters
+/// in both directions.
+///
+/// ## Example
+///
+/// ```no_run
+/// use serialport::SoftwareFlowControl;
+///
+/// // Only pause our output when the device sends its stop character
+/// let settings = SoftwareFlowControl {
+///     xon: b'Q',
+///     xoff: b'S',
+///     input: false,
+///     ..SoftwareFlowControl::default()
+/// };
+/// let port = serialport::new("/dev/ttyUSB0", 9600)
+///     .software_flow_control(settings)
+///     .open_native()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SoftwareFlowControl {
+    /// The character that resumes transmission, `VSTART` in termios
+    pub xon: u8,
+    /// The character that pauses transmission, `VSTOP` in termios
+    pub xoff: u8,
+    /// Whether any received character resumes paused output, not just `xon`
+    pub xany: bool,
+    /// Whether to send `xoff` and `xon` when the input buffer fills up and drains
+    pub input: bool,
+    /// Whether to pause output when `xoff` is received
+    pub output: bool,
+}
+
+impl Default for SoftwareFlowControl {
+    fn default() -> SoftwareFlowControl {
+        SoftwareFlowControl {
+            xon: 0x11,
+            xoff: 0x13,
+            xany: false,
+            input: true,
+            output: true,
+        }
+    }
+}
+
+/// Specifies when a read from a `TTYPort` completes
+///
+/// Regardless of the mode, a read first waits up to the port's read timeout for data to arrive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadMode {
+    /// Return as soon as any data is available
+    Immediate,
+    /// Keep reading until `min_bytes` have been received or no data has arrived for
+    /// `inter_byte_timeout`
+    ///
+    /// A `min_bytes` of 0 completes the read only once the buffer is full or the line goes idle.
+    ///
+    /// When `inter_byte_timeout` is a whole number of tenths of a second of at most 25.5s, this
+    /// is implemented by the kernel using the `VMIN` and `VTIME` termios settings. Other gaps are
+    /// emulated by polling the port.
+    Packet {
+        /// Number of bytes after which the read completes
+        min_bytes: u8,
+        /// Maximum gap between received bytes before the read completes
+        inter_byte_timeout: Duration,
+    },
+}
+
+impl ReadMode {
+    /// Returns the `VMIN` and `VTIME` values that implement this mode, if the kernel can
+    fn vmin_vtime(self) -> Option<(u8, u8)> {
+        const DECISECOND: u128 = 100_000_000;
+
+        match self {
+            ReadMode::Immediate => Some((1, 0)),
+            ReadMode::Packet {
+                min_bytes,
+                inter_byte_timeout,
+            } => {
+                let nanos = inter_byte_timeout.as_nanos();
+                if min_bytes == 0 || nanos == 0 || nanos % DECISECOND != 0 {
+                    return None;
+                }
+                match nanos / DECISECOND {
+                    vtime if vtime <= u128::from(u8::MAX) => Some((min_bytes, vtime as u8)),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
 impl TTYPort {
     /// Opens a TTY device as a serial port.
     ///
@@ -67,7 +256,7 @@ impl TTYPort {
     /// ## Errors
     ///
     /// * `NoDevice` if the device could not be opened. This could indicate that
-    ///    the device is already in use.
+    ///   the device is already in use.
     /// * `InvalidInput` if `path` is not a valid device name.
     /// * `Io` for any other error while opening or initializing the device.
     pub fn open(builder: &SerialPortBuilder) -> Result<TTYPort> {
@@ -122,22 +311,30 @@ impl TTYPort {
 
             unsafe { tcflush(fd, libc::TCIOFLUSH) };
 
-            // clear O_NONBLOCK flag
-            fcntl(fd, F_SETFL(nix::fcntl::OFlag::empty()))?;
+            // clear O_NONBLOCK flag unless a non-blocking port was requested
+            let flags = if builder.nonblocking {
+                nix::fcntl::OFlag::O_NONBLOCK
+            } else {
+                nix::fcntl::OFlag::empty()
+            };
+            fcntl(fd, F_SETFL(flags))?;
 
             Ok(())
         }
-        .map_err(|e: Error| {
-            close(fd);
-            e
-        })?;
+        .inspect_err(|_: &Error| close(fd))?;
 
         // Configure the low-level port settings
         let mut termios = termios::get_termios(fd)?;
-        termios::set_parity(&mut termios, builder.parity);
+        termios::set_parity(&mut termios, builder.parity).inspect_err(|_| close(fd))?;
         termios::set_flow_control(&mut termios, builder.flow_control);
+        if let Some(settings) = builder.software_flow_control {
+            termios::set_software_flow_control(&mut termios, settings);
+        }
         termios::set_data_bits(&mut termios, builder.data_bits);
-        termios::set_stop_bits(&mut termios, builder.stop_bits);
+        termios::set_stop_bits(&mut termios, builder.stop_bits).inspect_err(|_| close(fd))?;
+        termios::set_line_error_policy(&mut termios, builder.line_errors);
+        let (vmin, vtime) = builder.read_mode.vmin_vtime().unwrap_or((1, 0));
+        termios::set_vmin_vtime(&mut termios, vmin, vtime);
         #[cfg(not(any(target_os = "ios", target_os = "macos")))]
         termios::set_baud_rate(&mut termios, builder.baud_rate);
         #[cfg(any(target_os = "ios", target_os = "macos"))]
@@ -148,8 +345,17 @@ impl TTYPort {
         // Return the final port object
         Ok(TTYPort {
             fd,
-            timeout: builder.timeout,
+            read_timeout: builder.read_timeout,
+            write_timeout: builder.write_timeout,
             exclusive: false,
+            nonblocking: builder.nonblocking,
+            read_mode: builder.read_mode,
+            canceller: None,
+            line_errors: builder.line_errors,
+            output_suspended: false,
+            parmrk: ParmrkDecoder::default(),
+            multidrop_pending: VecDeque::new(),
+            tx_pending: Mutex::new(None),
             port_name: Some(builder.path.clone()),
             #[cfg(any(target_os = "ios", target_os = "macos"))]
             baud_rate: builder.baud_rate,
@@ -189,6 +395,417 @@ impl TTYPort {
         }
     }
 
+    /// Returns whether the port is in non-blocking mode
+    pub fn nonblocking(&self) -> bool {
+        self.nonblocking
+    }
+
+    /// Sets the port's non-blocking mode
+    ///
+    /// In non-blocking mode reads and writes no longer wait for the port to become ready, instead
+    /// they fail immediately with `io::ErrorKind::WouldBlock` and the port's timeouts are ignored.
+    /// This allows the port to be driven by an externally-owned `select`/`poll`/`epoll` loop using
+    /// its raw file descriptor.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` for any error while changing the file status flags of the port.
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<()> {
+        let flags = fcntl(self.fd, nix::fcntl::F_GETFL)?;
+        let mut flags = OFlag::from_bits_truncate(flags);
+        flags.set(OFlag::O_NONBLOCK, nonblocking);
+        fcntl(self.fd, nix::fcntl::F_SETFL(flags))?;
+        self.nonblocking = nonblocking;
+        Ok(())
+    }
+
+    /// Returns when reads from the port complete
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode
+    }
+
+    /// Sets when reads from the port complete
+    ///
+    /// See [`ReadMode`](enum.ReadMode.html) for the available modes. In non-blocking mode reads
+    /// always return whatever data is available.
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn set_read_mode(&mut self, read_mode: ReadMode) -> Result<()> {
+        let (vmin, vtime) = read_mode.vmin_vtime().unwrap_or((1, 0));
+        let mut termios = termios::get_termios(self.fd)?;
+        termios::set_vmin_vtime(&mut termios, vmin, vtime);
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        termios::set_termios(self.fd, &termios, self.baud_rate)?;
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        termios::set_termios(self.fd, &termios)?;
+        self.read_mode = read_mode;
+        Ok(())
+    }
+
+    /// Enables or disables 9-bit multi-drop mode
+    ///
+    /// Multi-drop buses use the parity bit as a 9th bit that marks address bytes. Enabling this
+    /// mode selects space parity and marks received bytes whose parity bit doesn't match, so that
+    /// `read_multidrop()` can tell address and data bytes apart. Disabling it turns parity off.
+    ///
+    /// This relies on mark and space parity, which is only supported on Linux and Android.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if mark and space parity are not supported on this platform.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn set_multidrop(&mut self, enabled: bool) -> Result<()> {
+        let mut termios = termios::get_termios(self.fd)?;
+        let parity = if enabled { Parity::Space } else { Parity::None };
+        termios::set_parity(&mut termios, parity)?;
+        if enabled {
+            termios::set_parity_marking(&mut termios, true);
+        } else {
+            termios::set_line_error_policy(&mut termios, self.line_errors);
+        }
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        termios::set_termios(self.fd, &termios, self.baud_rate)?;
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        termios::set_termios(self.fd, &termios)?;
+        self.parmrk = ParmrkDecoder::default();
+        self.multidrop_pending.clear();
+        Ok(())
+    }
+
+    /// Writes an address byte, i.e. a byte with the 9th bit set
+    ///
+    /// Any data still being transmitted is sent before switching to mark parity, so earlier bytes
+    /// keep their 9th bit. Likewise, data already received is classified with the previous parity
+    /// and kept for `read_multidrop()`.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if mark and space parity are not supported on this platform.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io(TimedOut)` if data still being transmitted wasn't sent within the write timeout.
+    /// * `Io` for any other type of I/O error.
+    pub fn write_address(&mut self, address: u8) -> Result<()> {
+        self.write_with_parity(Parity::Mark, &[address])
+    }
+
+    /// Writes data bytes, i.e. bytes with the 9th bit cleared
+    ///
+    /// Any data still being transmitted is sent before switching to space parity, so earlier
+    /// bytes keep their 9th bit. Likewise, data already received is classified with the previous
+    /// parity and kept for `read_multidrop()`.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if mark and space parity are not supported on this platform.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io(TimedOut)` if data still being transmitted wasn't sent within the write timeout.
+    /// * `Io` for any other type of I/O error.
+    pub fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        self.write_with_parity(Parity::Space, data)
+    }
+
+    fn write_with_parity(&mut self, parity: Parity, data: &[u8]) -> Result<()> {
+        if self.parity()? != parity {
+            // Received bytes were checked against the current parity, so they must be classified
+            // before it changes
+            self.queue_multidrop_input()?;
+            // The parity change applies to bytes still in the output queue as well, so they are
+            // sent first, waiting no longer than the write timeout
+            match self.write_timeout {
+                Some(timeout) => self.flush_timeout(timeout)?,
+                None => nix::sys::termios::tcdrain(self.fd)?,
+            }
+            self.set_parity(parity)?;
+        }
+        io::Write::write_all(self, data)?;
+        Ok(())
+    }
+
+    /// Returns whether received bytes are currently checked against mark parity, or `None` if
+    /// multi-drop mode isn't enabled
+    fn multidrop_mark(&self) -> Result<Option<bool>> {
+        let termios = termios::get_termios(self.fd)?;
+        Ok(match self.parity()? {
+            _ if termios.c_iflag & libc::PARMRK == 0 => None,
+            Parity::Mark => Some(true),
+            Parity::Space => Some(false),
+            _ => None,
+        })
+    }
+
+    /// Reads all input that is already available and classifies it with the current parity
+    fn queue_multidrop_input(&mut self) -> Result<()> {
+        let mark = match self.multidrop_mark()? {
+            Some(mark) => mark,
+            None => return Ok(()),
+        };
+        let mut raw = vec![0u8; ioctl::fionread(self.fd)? as usize];
+        if raw.is_empty() {
+            return Ok(());
+        }
+        let n = nix::unistd::read(self.fd, &mut raw)?;
+        self.queue_multidrop_bytes(&raw[..n], mark);
+        Ok(())
+    }
+
+    /// Decodes raw `PARMRK` input received with mark or space parity and queues the result
+    fn queue_multidrop_bytes(&mut self, raw: &[u8], mark: bool) {
+        for &byte in raw {
+            if let Some(decoded) = self.parmrk.push(byte) {
+                self.multidrop_pending
+                    .push_back(MultidropByte::classify(decoded, mark));
+            }
+        }
+    }
+
+    /// Moves queued bytes into `buf`, returning how many were moved
+    fn take_multidrop_bytes(&mut self, buf: &mut [MultidropByte]) -> usize {
+        let n = buf.len().min(self.multidrop_pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.multidrop_pending.drain(..n)) {
+            *dst = src;
+        }
+        n
+    }
+
+    /// Reads bytes received in 9-bit multi-drop mode, telling address and data bytes apart
+    ///
+    /// Multi-drop mode must have been enabled with `set_multidrop()`. Received bytes are
+    /// classified according to the parity in effect when they arrived, so this also works after
+    /// `write_address()` has switched to mark parity. Bytes that arrive while the parity is being
+    /// switched may still be misclassified. As with `read()`, this waits up to the read timeout
+    /// for data.
+    ///
+    /// Returns the number of bytes read into `buf`.
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if multi-drop mode isn't enabled.
+    /// * `TimedOut` if no data arrived within the read timeout.
+    /// * Any other error while reading from the port.
+    pub fn read_multidrop(&mut self, buf: &mut [MultidropByte]) -> io::Result<usize> {
+        let mark = self.multidrop_mark()?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Multi-drop mode is not enabled",
+            )
+        })?;
+
+        // Bytes received before the last parity switch are returned first
+        if !self.multidrop_pending.is_empty() {
+            return Ok(self.take_multidrop_bytes(buf));
+        }
+
+        self.read_marked(buf, |byte| MultidropByte::classify(byte, mark))
+    }
+
+    /// Returns the software flow control settings of the port
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn software_flow_control(&self) -> Result<SoftwareFlowControl> {
+        let termios = termios::get_termios(self.fd)?;
+        Ok(SoftwareFlowControl {
+            xon: termios.c_cc[libc::VSTART],
+            xoff: termios.c_cc[libc::VSTOP],
+            xany: termios.c_iflag & libc::IXANY == libc::IXANY,
+            input: termios.c_iflag & libc::IXOFF == libc::IXOFF,
+            output: termios.c_iflag & libc::IXON == libc::IXON,
+        })
+    }
+
+    /// Sets the software flow control settings of the port
+    ///
+    /// This leaves hardware flow control unchanged. Enabling both directions with the default
+    /// characters is equivalent to `FlowControl::Software`.
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn set_software_flow_control(&mut self, settings: SoftwareFlowControl) -> Result<()> {
+        let mut termios = termios::get_termios(self.fd)?;
+        termios::set_software_flow_control(&mut termios, settings);
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        return termios::set_termios(self.fd, &termios, self.baud_rate);
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        return termios::set_termios(self.fd, &termios);
+    }
+
+    /// Returns how bytes received with parity, framing or break errors are handled
+    ///
+    /// `None` means the port uses the platform's default handling.
+    pub fn line_error_policy(&self) -> Option<LineErrorPolicy> {
+        self.line_errors
+    }
+
+    /// Sets how bytes received with parity, framing or break errors are handled
+    ///
+    /// See [`LineErrorPolicy`](enum.LineErrorPolicy.html) for the available policies. `None`
+    /// restores the platform's default handling.
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn set_line_error_policy(&mut self, policy: Option<LineErrorPolicy>) -> Result<()> {
+        let mut termios = termios::get_termios(self.fd)?;
+        termios::set_line_error_policy(&mut termios, policy);
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        termios::set_termios(self.fd, &termios, self.baud_rate)?;
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        termios::set_termios(self.fd, &termios)?;
+        self.line_errors = policy;
+        self.parmrk = ParmrkDecoder::default();
+        Ok(())
+    }
+
+    /// Reads bytes along with any parity, framing or break error they were received with
+    ///
+    /// Errors are only reported with the `LineErrorPolicy::Replace` and `LineErrorPolicy::Report`
+    /// policies; otherwise every byte is returned without an error. As with `read()`, this waits
+    /// up to the read timeout for data.
+    ///
+    /// Returns the number of bytes read into `buf`.
+    ///
+    /// ## Errors
+    ///
+    /// * `TimedOut` if no data arrived within the read timeout.
+    /// * Any other error while reading from the port.
+    pub fn read_with_errors(&mut self, buf: &mut [ReceivedByte]) -> io::Result<usize> {
+        let substitute = match self.line_errors {
+            Some(LineErrorPolicy::Replace(substitute)) => Some(substitute),
+            Some(LineErrorPolicy::Report) => None,
+            _ => {
+                let mut raw = vec![0u8; buf.len()];
+                let n = io::Read::read(self, &mut raw)?;
+                for (received, &value) in buf.iter_mut().zip(&raw[..n]) {
+                    *received = ReceivedByte { value, error: None };
+                }
+                return Ok(n);
+            }
+        };
+
+        self.read_marked(buf, |byte| match byte {
+            MarkedByte::Valid(value) => ReceivedByte { value, error: None },
+            MarkedByte::Error(value) => ReceivedByte {
+                value: substitute.unwrap_or(value),
+                // A break is received as a NUL with a framing error
+                error: Some(if value == 0 {
+                    LineError::Break
+                } else {
+                    LineError::ParityOrFraming
+                }),
+            },
+        })
+    }
+
+    /// Reads into `buf`, removing the `PARMRK` marking and converting each byte with `convert`
+    ///
+    /// Keeps reading until at least one byte has been decoded.
+    fn read_marked<T>(
+        &mut self,
+        buf: &mut [T],
+        mut convert: impl FnMut(MarkedByte) -> T,
+    ) -> io::Result<usize> {
+        // Every decoded byte takes up at least one byte of input
+        let mut raw = vec![0u8; buf.len()];
+        let mut count = 0;
+        while count == 0 && !buf.is_empty() {
+            let n = io::Read::read(self, &mut raw)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            for &byte in &raw[..n] {
+                if let Some(decoded) = self.parmrk.push(byte) {
+                    buf[count] = convert(decoded);
+                    count += 1;
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns a handle that can cancel blocking reads and writes on this port from another thread
+    ///
+    /// The handle is created on first use and shared by every later call, as well as by ports
+    /// cloned from this one afterwards with `try_clone()` or `try_clone_native()`. Once cancelled,
+    /// reads and writes that are waiting for the port fail with `io::ErrorKind::ConnectionAborted`
+    /// until the handle is reset. Non-blocking reads and writes are not affected.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the pipe backing the handle could not be created.
+    pub fn cancel_handle(&mut self) -> Result<Canceller> {
+        match self.canceller {
+            Some(ref canceller) => Ok(canceller.clone()),
+            None => {
+                let canceller = Canceller::new()?;
+                self.canceller = Some(canceller.clone());
+                Ok(canceller)
+            }
+        }
+    }
+
+    fn cancel_fd(&self) -> Option<RawFd> {
+        self.canceller.as_ref().map(Canceller::as_raw_fd)
+    }
+
+    fn io_params(&self) -> IoParams {
+        IoParams {
+            fd: self.fd,
+            nonblocking: self.nonblocking,
+            read_timeout: self.read_timeout,
+            write_timeout: self.write_timeout,
+            read_mode: self.read_mode,
+            cancel_fd: self.cancel_fd(),
+        }
+    }
+
+    /// Reads a single burst of data along with the time it started arriving
+    ///
+    /// This waits up to the port's read timeout for data, recording the time as soon as the port
+    /// becomes readable. It then keeps reading until `buf` is full or no data has arrived for
+    /// `gap`, so that each call returns approximately one burst on the wire. Data arriving after
+    /// the gap is left for the next call.
+    ///
+    /// Returns the number of bytes read into `buf` and when they arrived. The timestamps are only
+    /// accurate if this is called again promptly, as data that is already waiting when the port is
+    /// read is timestamped on the call rather than on its arrival.
+    ///
+    /// This is intended for use with `ReadMode::Immediate`. A `ReadMode::Packet` that is
+    /// implemented by the kernel still applies, as the kernel holds back data until `min_bytes`
+    /// have arrived or the line has gone idle. This can delay the timestamp and merge several
+    /// bursts into one. An emulated `ReadMode::Packet` does not apply to these reads.
+    ///
+    /// ## Errors
+    ///
+    /// * `TimedOut` if no data arrived within the read timeout.
+    /// * `WouldBlock` if no data is available in non-blocking mode.
+    /// * Any other error while reading from the port.
+    pub fn read_timestamped(
+        &mut self,
+        buf: &mut [u8],
+        gap: Duration,
+    ) -> io::Result<(usize, ReadTimestamp)> {
+        if !self.nonblocking {
+            super::poll::wait_read_fd(self.fd, self.read_timeout, self.cancel_fd())?;
+        }
+        let timestamp = ReadTimestamp {
+            instant: Instant::now(),
+            system_time: SystemTime::now(),
+        };
+
+        let count = nix::unistd::read(self.fd, buf).map_err(|e| io::Error::from(Error::from(e)))?;
+        let count = self.io_params().read_until_gap(buf, count, buf.len(), gap);
+        Ok((count, timestamp))
+    }
+
     fn set_pin(&mut self, pin: ioctl::SerialLines, level: bool) -> Result<()> {
         if level {
             ioctl::tiocmbis(self.fd, pin)
@@ -274,8 +891,17 @@ impl TTYPort {
 
         let slave_tty = TTYPort {
             fd,
-            timeout: Duration::from_millis(100),
+            read_timeout: Some(Duration::from_millis(100)),
+            write_timeout: Some(Duration::from_millis(100)),
             exclusive: true,
+            nonblocking: false,
+            read_mode: ReadMode::Immediate,
+            canceller: None,
+            line_errors: None,
+            output_suspended: false,
+            parmrk: ParmrkDecoder::default(),
+            multidrop_pending: VecDeque::new(),
+            tx_pending: Mutex::new(None),
             port_name: Some(ptty_name),
             #[cfg(any(target_os = "ios", target_os = "macos"))]
             baud_rate,
@@ -286,8 +912,17 @@ impl TTYPort {
         // BSDs when used on the master port.
         let master_tty = TTYPort {
             fd: next_pty_fd.into_raw_fd(),
-            timeout: Duration::from_millis(100),
+            read_timeout: Some(Duration::from_millis(100)),
+            write_timeout: Some(Duration::from_millis(100)),
             exclusive: true,
+            nonblocking: false,
+            read_mode: ReadMode::Immediate,
+            canceller: None,
+            line_errors: None,
+            output_suspended: false,
+            parmrk: ParmrkDecoder::default(),
+            multidrop_pending: VecDeque::new(),
+            tx_pending: Mutex::new(None),
             port_name: None,
             #[cfg(any(target_os = "ios", target_os = "macos"))]
             baud_rate,
@@ -305,6 +940,212 @@ impl TTYPort {
         .map_err(|e| e.into())
     }
 
+    /// Suspends transmission of data written to the port
+    ///
+    /// Written data is queued until `resume_output()` is called. Once the output queue is full,
+    /// writes wait or time out as they would with hardware flow control.
+    pub fn suspend_output(&mut self) -> Result<()> {
+        nix::sys::termios::tcflow(self.fd, FlowArg::TCOOFF)?;
+        self.output_suspended = true;
+        Ok(())
+    }
+
+    /// Resumes transmission after `suspend_output()`, or after an XOFF character was received
+    pub fn resume_output(&mut self) -> Result<()> {
+        nix::sys::termios::tcflow(self.fd, FlowArg::TCOON)?;
+        self.output_suspended = false;
+        Ok(())
+    }
+
+    /// Returns whether output has been suspended with `suspend_output()`
+    ///
+    /// Output paused by the kernel because an XOFF character was received is not reported, as
+    /// the kernel doesn't expose this state.
+    pub fn output_suspended(&self) -> bool {
+        self.output_suspended
+    }
+
+    /// Sends the XOFF character, asking the other end to stop transmitting
+    ///
+    /// This uses the character set with `set_software_flow_control()`, DC3 by default, and works
+    /// whether or not software flow control is enabled.
+    pub fn send_xoff(&self) -> Result<()> {
+        nix::sys::termios::tcflow(self.fd, FlowArg::TCIOFF).map_err(|e| e.into())
+    }
+
+    /// Sends the XON character, asking the other end to resume transmitting
+    ///
+    /// This uses the character set with `set_software_flow_control()`, DC1 by default, and works
+    /// whether or not software flow control is enabled.
+    pub fn send_xon(&self) -> Result<()> {
+        nix::sys::termios::tcflow(self.fd, FlowArg::TCION).map_err(|e| e.into())
+    }
+
+    /// Waits until all data written to the port has been transmitted, or the timeout elapses
+    ///
+    /// Unlike `flush()`, which waits indefinitely, this gives up once `timeout` has passed, e.g.
+    /// when hardware flow control is holding off the transmitter. The output is done once the
+    /// kernel's output queue is empty and, on Linux serial ports that report it, the UART's
+    /// transmitter is empty as well.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io(TimedOut)` if the output wasn't transmitted in time. The error description includes
+    ///   the number of bytes still queued.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn flush_timeout(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let queued = ioctl::tiocoutq(self.fd)?;
+            if queued == 0 && self.transmitter_empty()?.unwrap_or(true) {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::new(
+                    ErrorKind::Io(io::ErrorKind::TimedOut),
+                    format!(
+                        "Flush timed out with {} bytes still queued for transmission",
+                        queued
+                    ),
+                ));
+            }
+            thread::sleep(FLUSH_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
+    /// Returns whether all data written to the port has physically left the UART
+    ///
+    /// This requires both the kernel's output queue and the UART's transmitter, including its
+    /// shift register, to be empty. Where the driver doesn't report the transmitter status, as
+    /// with ptys and on platforms other than Linux, the output is instead considered transmitted
+    /// once the time it takes to send one character at the current baud rate and frame format has
+    /// passed since the last write, or since the output queue was last seen holding data.
+    ///
+    /// ## Errors
+    ///
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn tx_empty(&self) -> Result<bool> {
+        let mut tx_pending = self.tx_pending();
+        if ioctl::tiocoutq(self.fd)? != 0 {
+            *tx_pending = Some(Instant::now());
+            return Ok(false);
+        }
+        if let Some(empty) = self.transmitter_empty()? {
+            return Ok(empty);
+        }
+
+        // Nothing has been written yet if no time was recorded
+        match *tx_pending {
+            Some(pending) => Ok(pending.elapsed() >= self.character_time()?),
+            None => Ok(true),
+        }
+    }
+
+    /// Locks the last time output was known to be waiting for transmission
+    fn tx_pending(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.tx_pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records that data was just written, for estimating when it has been transmitted
+    fn mark_tx_pending(&self) {
+        *self.tx_pending() = Some(Instant::now());
+    }
+
+    /// Waits until all data written to the port has physically left the UART, or the timeout
+    /// elapses
+    ///
+    /// This is useful for half-duplex links, where the line must not be turned around before the
+    /// last stop bit has been sent. Where the driver doesn't report the transmitter status, this
+    /// waits for the output queue to drain and then for the time it takes to send one more
+    /// character at the current baud rate and frame format, as long as the timeout allows.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io(TimedOut)` if the output wasn't transmitted in time. The error description includes
+    ///   the number of bytes still queued.
+    /// * `NoDevice` if the device was disconnected.
+    /// * `Io` for any other type of I/O error.
+    pub fn wait_tx_complete(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        self.flush_timeout(timeout)?;
+        if self.transmitter_empty()?.is_none() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            thread::sleep(self.character_time()?.min(remaining));
+        }
+        Ok(())
+    }
+
+    /// Returns whether the UART has finished shifting out the last byte, or `None` if the driver
+    /// doesn't report this
+    fn transmitter_empty(&self) -> Result<Option<bool>> {
+        #[cfg(target_os = "linux")]
+        return ioctl::tiocsergetlsr(self.fd);
+        #[cfg(not(target_os = "linux"))]
+        return Ok(None);
+    }
+
+    /// Changes the parity in `termios`, keeping the handling of errors chosen by the line error
+    /// policy
+    fn update_parity(&self, termios: &mut termios::Termios, parity: Parity) -> Result<()> {
+        let ignpar = termios.c_iflag & libc::IGNPAR;
+        termios::set_parity(termios, parity)?;
+        if self.line_errors.is_some() {
+            termios.c_iflag = (termios.c_iflag & !libc::IGNPAR) | ignpar;
+        }
+        Ok(())
+    }
+
+    /// Returns the time taken to transmit a single character with the current settings
+    fn character_time(&self) -> Result<Duration> {
+        let data_bits = match self.data_bits()? {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        };
+        let parity_bits = match self.parity()? {
+            Parity::None => 0,
+            Parity::Odd | Parity::Even | Parity::Mark | Parity::Space => 1,
+        };
+        // Counted in half bits to allow for 1.5 stop bits
+        let stop_half_bits = match self.stop_bits()? {
+            StopBits::One => 2,
+            StopBits::OnePointFive => 3,
+            StopBits::Two => 4,
+        };
+        // Each character is preceded by a start bit
+        let half_bits = 2 * (1 + data_bits + parity_bits) + stop_half_bits;
+        match self.baud_rate()? {
+            0 => Ok(Duration::from_secs(0)),
+            baud_rate => Ok(Duration::from_nanos(
+                half_bits * 500_000_000 / u64::from(baud_rate),
+            )),
+        }
+    }
+
+    /// Splits the port into a reading half, a writing half and a handle for its settings.
+    ///
+    /// All three share this port, so settings such as the timeouts changed through the
+    /// `SettingsHandle` apply to both halves. Reads and writes only lock the port to look up its
+    /// current settings, so they don't wait for each other. The port is closed once all three
+    /// have been dropped.
+    ///
+    /// This is the same as `SerialPort::split()` but can't fail.
+    pub fn split(self) -> (ReadHalf, WriteHalf, SettingsHandle) {
+        let port = Arc::new(Mutex::new(self));
+        (
+            ReadHalf::new(SharedTTYPort(port.clone())),
+            WriteHalf::new(SharedTTYPort(port.clone())),
+            SettingsHandle::new(port),
+        )
+    }
+
     /// Attempts to clone the `SerialPort`. This allow you to write and read simultaneously from the
     /// same serial connection. Please note that if you want a real asynchronous serial port you
     /// should look at [mio-serial](https://crates.io/crates/mio-serial) or
@@ -312,7 +1153,8 @@ impl TTYPort {
     ///
     /// Also, you must be very careful when changing the settings of a cloned `SerialPort` : since
     /// the settings are cached on a per object basis, trying to modify them from two different
-    /// objects can cause some nasty behavior.
+    /// objects can cause some nasty behavior. Since `Read` and `Write` are also implemented for
+    /// `&TTYPort`, sharing a single port between threads, e.g. through an `Arc`, avoids this.
     ///
     /// This is the same as `SerialPort::try_clone()` but returns the concrete type instead.
     ///
@@ -324,14 +1166,190 @@ impl TTYPort {
         Ok(TTYPort {
             fd: fd_cloned,
             exclusive: self.exclusive,
+            nonblocking: self.nonblocking,
+            read_mode: self.read_mode,
+            canceller: self.canceller.clone(),
+            line_errors: self.line_errors,
+            output_suspended: self.output_suspended,
+            parmrk: ParmrkDecoder::default(),
+            multidrop_pending: VecDeque::new(),
+            tx_pending: Mutex::new(None),
             port_name: self.port_name.clone(),
-            timeout: self.timeout,
+            read_timeout: self.read_timeout,
+            write_timeout: self.write_timeout,
             #[cfg(any(target_os = "ios", target_os = "macos"))]
             baud_rate: self.baud_rate,
         })
     }
 }
 
+/// The settings that reads and writes on a `TTYPort` depend on
+///
+/// These are copied out of the port for each operation, so that the halves of a split port
+/// don't hold its lock while waiting. The file descriptors stay valid as the shared port owns
+/// them for as long as either half exists.
+#[derive(Clone, Copy, Debug)]
+struct IoParams {
+    fd: RawFd,
+    nonblocking: bool,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    read_mode: ReadMode,
+    cancel_fd: Option<RawFd>,
+}
+
+impl IoParams {
+    fn read(self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.nonblocking {
+            return nix::unistd::read(self.fd, buf).map_err(|e| io::Error::from(Error::from(e)));
+        }
+
+        if let Err(e) = super::poll::wait_read_fd(self.fd, self.read_timeout, self.cancel_fd) {
+            return Err(io::Error::from(Error::from(e)));
+        }
+
+        nix::unistd::read(self.fd, buf)
+            .map(|count| self.read_packet(buf, count))
+            .map_err(|e| io::Error::from(Error::from(e)))
+    }
+
+    // The read completes as soon as any data is available, the emulated `ReadMode::Packet`
+    // conditions only apply to `read()`
+    fn read_vectored(self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        if !self.nonblocking {
+            super::poll::wait_read_fd(self.fd, self.read_timeout, self.cancel_fd)?;
+        }
+
+        // `IoSliceMut` is guaranteed to be ABI compatible with `iovec` on Unix
+        let res = unsafe {
+            libc::readv(
+                self.fd,
+                bufs.as_ptr() as *const libc::iovec,
+                bufs.len().min(IOV_MAX) as libc::c_int,
+            )
+        };
+        nix::errno::Errno::result(res)
+            .map(|count| count as usize)
+            .map_err(|e| io::Error::from(Error::from(e)))
+    }
+
+    fn write(self, buf: &[u8]) -> io::Result<usize> {
+        if !self.nonblocking {
+            if let Err(e) = super::poll::wait_write_fd(self.fd, self.write_timeout, self.cancel_fd)
+            {
+                return Err(io::Error::from(Error::from(e)));
+            }
+        }
+
+        nix::unistd::write(self.fd, buf).map_err(|e| io::Error::from(Error::from(e)))
+    }
+
+    fn write_vectored(self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        if !self.nonblocking {
+            super::poll::wait_write_fd(self.fd, self.write_timeout, self.cancel_fd)?;
+        }
+
+        // `IoSlice` is guaranteed to be ABI compatible with `iovec` on Unix
+        let res = unsafe {
+            libc::writev(
+                self.fd,
+                bufs.as_ptr() as *const libc::iovec,
+                bufs.len().min(IOV_MAX) as libc::c_int,
+            )
+        };
+        nix::errno::Errno::result(res)
+            .map(|count| count as usize)
+            .map_err(|e| io::Error::from(Error::from(e)))
+    }
+
+    fn flush(self) -> io::Result<()> {
+        nix::sys::termios::tcdrain(self.fd).map_err(|e| io::Error::from(Error::from(e)))
+    }
+
+    /// Continues a read until the `ReadMode::Packet` conditions are met, for modes that can't be
+    /// implemented with `VMIN` and `VTIME`
+    ///
+    /// `count` bytes have already been read into `buf`.
+    fn read_packet(self, buf: &mut [u8], count: usize) -> usize {
+        let (min_bytes, inter_byte_timeout) = match self.read_mode {
+            ReadMode::Packet {
+                min_bytes,
+                inter_byte_timeout,
+            } if self.read_mode.vmin_vtime().is_none() => (min_bytes, inter_byte_timeout),
+            _ => return count,
+        };
+        let min_bytes = match min_bytes {
+            0 => buf.len(),
+            n => buf.len().min(n as usize),
+        };
+        self.read_until_gap(buf, count, min_bytes, inter_byte_timeout)
+    }
+
+    /// Keeps reading into `buf` after `count` bytes until `min_bytes` have been read or no data
+    /// has arrived for `gap`
+    ///
+    /// Errors are deferred to the next read so that no received data is lost.
+    fn read_until_gap(
+        self,
+        buf: &mut [u8],
+        mut count: usize,
+        min_bytes: usize,
+        gap: Duration,
+    ) -> usize {
+        while count > 0 && count < min_bytes {
+            if super::poll::wait_read_fd(self.fd, Some(gap), self.cancel_fd).is_err() {
+                break;
+            }
+            match nix::unistd::read(self.fd, &mut buf[count..]) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => count += n,
+            }
+        }
+        count
+    }
+}
+
+/// A half of a split `TTYPort`, doing I/O with the shared port's current settings
+struct SharedTTYPort(Arc<Mutex<TTYPort>>);
+
+impl SharedTTYPort {
+    fn lock(&self) -> MutexGuard<'_, TTYPort> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn io_params(&self) -> IoParams {
+        self.lock().io_params()
+    }
+}
+
+impl io::Read for SharedTTYPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io_params().read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.io_params().read_vectored(bufs)
+    }
+}
+
+impl io::Write for SharedTTYPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.io_params().write(buf)?;
+        self.lock().mark_tx_pending();
+        Ok(count)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let count = self.io_params().write_vectored(bufs)?;
+        self.lock().mark_tx_pending();
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io_params().flush()
+    }
+}
+
 impl Drop for TTYPort {
     fn drop(&mut self) {
         close(self.fd);
@@ -370,8 +1388,19 @@ impl FromRawFd for TTYPort {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
         TTYPort {
             fd,
-            timeout: Duration::from_millis(100),
+            read_timeout: Some(Duration::from_millis(100)),
+            write_timeout: Some(Duration::from_millis(100)),
             exclusive: ioctl::tiocexcl(fd).is_ok(),
+            nonblocking: fcntl(fd, nix::fcntl::F_GETFL)
+                .map(|flags| OFlag::from_bits_truncate(flags).contains(OFlag::O_NONBLOCK))
+                .unwrap_or(false),
+            read_mode: ReadMode::Immediate,
+            canceller: None,
+            line_errors: None,
+            output_suspended: false,
+            parmrk: ParmrkDecoder::default(),
+            multidrop_pending: VecDeque::new(),
+            tx_pending: Mutex::new(None),
             // It is not trivial to get the file path corresponding to a file descriptor.
             // We'll punt on it and set it to `None` here.
             port_name: None,
@@ -384,28 +1413,94 @@ impl FromRawFd for TTYPort {
     }
 }
 
+/// Allows a `TTYPort` to be registered with a `mio::Poll` instance.
+///
+/// Registering the port switches it into non-blocking mode, where reads and writes return
+/// `WouldBlock` instead of waiting for the port to become ready. The port stays in non-blocking
+/// mode after it is deregistered, use `TTYPort::set_nonblocking(false)` to restore blocking I/O.
+///
+/// This is only available when the `mio` feature is enabled.
+#[cfg(feature = "mio")]
+impl mio::event::Source for TTYPort {
+    fn register(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        self.set_nonblocking(true)?;
+        mio::unix::SourceFd(&self.fd).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &mio::Registry,
+        token: mio::Token,
+        interests: mio::Interest,
+    ) -> io::Result<()> {
+        mio::unix::SourceFd(&self.fd).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+        mio::unix::SourceFd(&self.fd).deregister(registry)
+    }
+}
+
+/// Reads from a shared reference, allowing one thread to read while another writes to the same
+/// port, as with `TcpStream`.
+///
+/// The settings used for the read, such as the read timeout, are those of the referenced port.
+impl io::Read for &TTYPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io_params().read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.io_params().read_vectored(bufs)
+    }
+}
+
+/// Writes to a shared reference, allowing one thread to write while another reads from the same
+/// port, as with `TcpStream`.
+impl io::Write for &TTYPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.io_params().write(buf)?;
+        self.mark_tx_pending();
+        Ok(count)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let count = self.io_params().write_vectored(bufs)?;
+        self.mark_tx_pending();
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io_params().flush()
+    }
+}
+
 impl io::Read for TTYPort {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if let Err(e) = super::poll::wait_read_fd(self.fd, self.timeout) {
-            return Err(io::Error::from(Error::from(e)));
-        }
+        (&*self).read(buf)
+    }
 
-        nix::unistd::read(self.fd, buf).map_err(|e| io::Error::from(Error::from(e)))
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
     }
 }
 
 impl io::Write for TTYPort {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        if let Err(e) = super::poll::wait_write_fd(self.fd, self.timeout) {
-            return Err(io::Error::from(Error::from(e)));
-        }
+        (&*self).write(buf)
+    }
 
-        nix::unistd::write(self.fd, buf).map_err(|e| io::Error::from(Error::from(e)))
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        nix::sys::termios::tcdrain(self.fd)
-            .map_err(|_| io::Error::new(io::ErrorKind::Other, "flush failed"))
+        (&*self).flush()
     }
 }
 
@@ -442,7 +1537,7 @@ impl SerialPort for TTYPort {
     /// On some platforms this will be the actual device baud rate, which may differ from the
     /// desired baud rate.
     #[cfg(any(
-        target_os = "dragonflybsd",
+        target_os = "dragonfly",
         target_os = "freebsd",
         target_os = "netbsd",
         target_os = "openbsd"
@@ -559,8 +1654,16 @@ impl SerialPort for TTYPort {
 
     fn parity(&self) -> Result<Parity> {
         let termios = termios::get_termios(self.fd)?;
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        let mark_space = termios.c_cflag & libc::CMSPAR == libc::CMSPAR;
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        let mark_space = false;
+
         if termios.c_cflag & libc::PARENB == libc::PARENB {
-            if termios.c_cflag & libc::PARODD == libc::PARODD {
+            let odd = termios.c_cflag & libc::PARODD == libc::PARODD;
+            if mark_space {
+                Ok(if odd { Parity::Mark } else { Parity::Space })
+            } else if odd {
                 Ok(Parity::Odd)
             } else {
                 Ok(Parity::Even)
@@ -573,19 +1676,27 @@ impl SerialPort for TTYPort {
     fn stop_bits(&self) -> Result<StopBits> {
         let termios = termios::get_termios(self.fd)?;
         if termios.c_cflag & libc::CSTOPB == libc::CSTOPB {
-            Ok(StopBits::Two)
+            if termios.c_cflag & libc::CSIZE == libc::CS5 {
+                Ok(StopBits::OnePointFive)
+            } else {
+                Ok(StopBits::Two)
+            }
         } else {
             Ok(StopBits::One)
         }
     }
 
-    fn timeout(&self) -> Duration {
-        self.timeout
+    fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+
+    fn write_timeout(&self) -> Option<Duration> {
+        self.write_timeout
     }
 
     #[cfg(any(
         target_os = "android",
-        target_os = "dragonflybsd",
+        target_os = "dragonfly",
         target_os = "freebsd",
         target_os = "netbsd",
         target_os = "openbsd",
@@ -616,7 +1727,7 @@ impl SerialPort for TTYPort {
 
     fn set_parity(&mut self, parity: Parity) -> Result<()> {
         let mut termios = termios::get_termios(self.fd)?;
-        termios::set_parity(&mut termios, parity);
+        self.update_parity(&mut termios, parity)?;
         #[cfg(any(target_os = "ios", target_os = "macos"))]
         return termios::set_termios(self.fd, &termios, self.baud_rate);
         #[cfg(not(any(target_os = "ios", target_os = "macos")))]
@@ -624,8 +1735,11 @@ impl SerialPort for TTYPort {
     }
 
     fn set_data_bits(&mut self, data_bits: DataBits) -> Result<()> {
+        let stop_bits = self.stop_bits()?;
         let mut termios = termios::get_termios(self.fd)?;
         termios::set_data_bits(&mut termios, data_bits);
+        // Otherwise `CSTOPB` would silently switch between 1.5 and 2 stop bits
+        termios::set_stop_bits(&mut termios, stop_bits)?;
         #[cfg(any(target_os = "ios", target_os = "macos"))]
         return termios::set_termios(self.fd, &termios, self.baud_rate);
         #[cfg(not(any(target_os = "ios", target_os = "macos")))]
@@ -634,15 +1748,41 @@ impl SerialPort for TTYPort {
 
     fn set_stop_bits(&mut self, stop_bits: StopBits) -> Result<()> {
         let mut termios = termios::get_termios(self.fd)?;
-        termios::set_stop_bits(&mut termios, stop_bits);
+        termios::set_stop_bits(&mut termios, stop_bits)?;
         #[cfg(any(target_os = "ios", target_os = "macos"))]
         return termios::set_termios(self.fd, &termios, self.baud_rate);
         #[cfg(not(any(target_os = "ios", target_os = "macos")))]
         return termios::set_termios(self.fd, &termios);
     }
 
-    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
-        self.timeout = timeout;
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.read_timeout = timeout;
+        Ok(())
+    }
+
+    fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.write_timeout = timeout;
+        Ok(())
+    }
+
+    fn apply_settings(&mut self, settings: &SerialSettings, when: ApplyWhen) -> Result<()> {
+        let mut termios = termios::get_termios(self.fd)?;
+        termios::set_data_bits(&mut termios, settings.data_bits);
+        self.update_parity(&mut termios, settings.parity)?;
+        termios::set_stop_bits(&mut termios, settings.stop_bits)?;
+        termios::set_flow_control(&mut termios, settings.flow_control);
+        #[cfg(any(target_os = "ios", target_os = "macos"))]
+        {
+            termios::set_termios_when(self.fd, &termios, settings.baud_rate, when)?;
+            self.baud_rate = settings.baud_rate;
+        }
+        #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+        {
+            termios::set_baud_rate(&mut termios, settings.baud_rate);
+            termios::set_termios_when(self.fd, &termios, when)?;
+        }
+        self.read_timeout = settings.read_timeout;
+        self.write_timeout = settings.write_timeout;
         Ok(())
     }
 
@@ -692,6 +1832,10 @@ impl SerialPort for TTYPort {
             .map_err(|e| e.into())
     }
 
+    fn split(self: Box<Self>) -> Result<(ReadHalf, WriteHalf, SettingsHandle)> {
+        Ok(TTYPort::split(*self))
+    }
+
     fn try_clone(&self) -> Result<Box<dyn SerialPort>> {
         match self.try_clone_native() {
             Ok(p) => Ok(Box::new(p)),
@@ -736,3 +1880,51 @@ fn test_ttyport_into_raw_fd() {
     close(master_fd);
     close(slave_fd);
 }
+
+#[test]
+fn test_multidrop_byte_classify() {
+    // With mark parity, a valid byte had its 9th bit set
+    assert_eq!(
+        MultidropByte::classify(MarkedByte::Valid(0x10), true),
+        MultidropByte::Address(0x10)
+    );
+    assert_eq!(
+        MultidropByte::classify(MarkedByte::Error(0x10), true),
+        MultidropByte::Data(0x10)
+    );
+    // With space parity, a parity error means the 9th bit was set
+    assert_eq!(
+        MultidropByte::classify(MarkedByte::Valid(0x10), false),
+        MultidropByte::Data(0x10)
+    );
+    assert_eq!(
+        MultidropByte::classify(MarkedByte::Error(0x10), false),
+        MultidropByte::Address(0x10)
+    );
+}
+
+#[test]
+fn test_multidrop_pending() {
+    #![allow(unused_variables)]
+    let (master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    // Input classified with space parity before a switch to mark parity
+    slave.queue_multidrop_bytes(&[0x10, 0xFF, 0x00, 0x20], false);
+    // An escaped 0xFF split across two reads, classified with mark parity
+    slave.queue_multidrop_bytes(&[0xFF], true);
+    slave.queue_multidrop_bytes(&[0xFF, 0x30], true);
+
+    let mut buf = [MultidropByte::Data(0); 3];
+    assert_eq!(slave.take_multidrop_bytes(&mut buf), 3);
+    assert_eq!(
+        buf,
+        [
+            MultidropByte::Data(0x10),
+            MultidropByte::Address(0x20),
+            MultidropByte::Address(0xFF),
+        ]
+    );
+    assert_eq!(slave.take_multidrop_bytes(&mut buf), 1);
+    assert_eq!(buf[0], MultidropByte::Address(0x30));
+    assert_eq!(slave.take_multidrop_bytes(&mut buf), 0);
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::{Error, ErrorKind, Result, SerialPort};
+
+/// How often the reader thread checks whether it has been stopped while the port is idle
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The size of the buffer each read is made into
+const CHUNK_SIZE: usize = 1024;
+
+/// An event delivered by a `SerialReader`
+#[derive(Debug)]
+pub enum ReadEvent {
+    /// Data received from the port
+    Data(Vec<u8>),
+
+    /// Reading from the port failed. The reader thread exits after reporting the error.
+    Error(io::Error),
+
+    /// The reader thread has exited because the device was disconnected or reading from it
+    /// failed. This is always the last event delivered.
+    Disconnected,
+}
+
+/// Reads from a serial port on a background thread
+///
+/// The reader takes ownership of a port and delivers everything received from it as
+/// `ReadEvent`s through a channel. This is commonly combined with `SerialPort::try_clone()` to
+/// read and write from different threads:
+///
+/// ```no_run
+/// use serialport::{ReadEvent, SerialReader};
+///
+/// let mut port = serialport::new("/dev/ttyUSB0", 9600).open().unwrap();
+/// let reader = SerialReader::new(port.try_clone().unwrap()).unwrap();
+///
+/// port.write_all(b"ping").unwrap();
+/// for event in reader.receiver() {
+///     match event {
+///         ReadEvent::Data(data) => println!("Received: {:?}", data),
+///         ReadEvent::Error(e) => eprintln!("{}", e),
+///         ReadEvent::Disconnected => break,
+///     }
+/// }
+/// ```
+///
+/// The thread exits once the port is disconnected, when reading fails, or when the reader is
+/// stopped with `stop()` or dropped. Stopping hands the port back to the caller.
+pub struct SerialReader {
+    receiver: Receiver<ReadEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Box<dyn SerialPort>>>,
+}
+
+impl fmt::Debug for SerialReader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SerialReader")
+            .field("receiver", &self.receiver)
+            .field("stop", &self.stop)
+            .field("thread", &self.thread)
+            .finish()
+    }
+}
+
+impl SerialReader {
+    /// Starts reading from `port` on a new thread
+    ///
+    /// While the reader owns the port its read timeout is set to 100ms, so that the thread notices
+    /// when it is stopped without spinning on shorter timeouts such as the default of zero. Data
+    /// is still delivered as soon as it arrives. The original timeout is restored when the port
+    /// is returned by `stop()`.
+    ///
+    /// ## Errors
+    ///
+    /// * `Io` if the read timeout could not be set or the thread could not be spawned.
+    pub fn new(mut port: Box<dyn SerialPort>) -> Result<SerialReader> {
+        let read_timeout = port.read_timeout();
+        port.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = thread::Builder::new()
+            .name("serialport-reader".into())
+            .spawn(move || {
+                read_loop(&mut *port, &sender, &thread_stop);
+                // The port is returned even if the timeout can't be restored
+                let _ = port.set_read_timeout(read_timeout);
+                port
+            })?;
+
+        Ok(SerialReader {
+            receiver,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the channel events from the port are delivered through
+    pub fn receiver(&self) -> &Receiver<ReadEvent> {
+        &self.receiver
+    }
+
+    /// Stops the reader thread and returns the port
+    ///
+    /// Events that were already delivered can no longer be received once the reader is stopped.
+    ///
+    /// ## Errors
+    ///
+    /// * `Unknown` if the reader thread panicked.
+    pub fn stop(mut self) -> Result<Box<dyn SerialPort>> {
+        self.join()
+            .expect("Reader thread is only joined once")
+            .map_err(|_| Error::new(ErrorKind::Unknown, "Serial reader thread panicked"))
+    }
+
+    fn join(&mut self) -> Option<thread::Result<Box<dyn SerialPort>>> {
+        self.stop.store(true, Ordering::SeqCst);
+        self.thread.take().map(JoinHandle::join)
+    }
+}
+
+impl Drop for SerialReader {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+fn read_loop(port: &mut dyn SerialPort, sender: &Sender<ReadEvent>, stop: &AtomicBool) {
+    let mut buf = [0u8; CHUNK_SIZE];
+    while !stop.load(Ordering::SeqCst) {
+        match port.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                // Nobody is listening anymore once the receiver is dropped
+                if sender.send(ReadEvent::Data(buf[..n].to_vec())).is_err() {
+                    return;
+                }
+            }
+            Err(ref e)
+                if e.kind() == io::ErrorKind::TimedOut
+                    || e.kind() == io::ErrorKind::Interrupted => {}
+            Err(ref e)
+                if e.kind() == io::ErrorKind::NotFound || e.kind() == io::ErrorKind::BrokenPipe =>
+            {
+                break
+            }
+            Err(e) => {
+                let _ = sender.send(ReadEvent::Error(e));
+                break;
+            }
+        }
+    }
+
+    if !stop.load(Ordering::SeqCst) {
+        let _ = sender.send(ReadEvent::Disconnected);
+    }
+}
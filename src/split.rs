@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::SerialPort;
+
+/// The reading half of a port split with `SerialPort::split()`
+///
+/// Reads use the port's current settings, including any changes made through the
+/// `SettingsHandle`.
+pub struct ReadHalf {
+    inner: Box<dyn io::Read + Send>,
+}
+
+/// The writing half of a port split with `SerialPort::split()`
+///
+/// Writes use the port's current settings, including any changes made through the
+/// `SettingsHandle`.
+pub struct WriteHalf {
+    inner: Box<dyn io::Write + Send>,
+}
+
+/// Shared access to the settings of a port split with `SerialPort::split()`
+///
+/// Changes made through the handle apply to both halves. The handle can be cloned to change
+/// settings from several threads.
+#[derive(Clone)]
+pub struct SettingsHandle {
+    port: Arc<Mutex<dyn SerialPort>>,
+}
+
+impl ReadHalf {
+    // Only `TTYPort` can be split so far
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub(crate) fn new<R: io::Read + Send + 'static>(inner: R) -> ReadHalf {
+        ReadHalf {
+            inner: Box::new(inner),
+        }
+    }
+}
+
+impl WriteHalf {
+    // Only `TTYPort` can be split so far
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub(crate) fn new<W: io::Write + Send + 'static>(inner: W) -> WriteHalf {
+        WriteHalf {
+            inner: Box::new(inner),
+        }
+    }
+}
+
+impl SettingsHandle {
+    // Only `TTYPort` can be split so far
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub(crate) fn new(port: Arc<Mutex<dyn SerialPort>>) -> SettingsHandle {
+        SettingsHandle { port }
+    }
+
+    /// Locks the port for reading or changing its settings
+    ///
+    /// Reads and writes through the halves wait while the port is locked, so the lock should
+    /// only be held briefly. Reading from or writing to the returned port directly is possible,
+    /// but blocks the halves until it completes.
+    pub fn lock(&self) -> MutexGuard<'_, dyn SerialPort + 'static> {
+        // The port holds no invariants that a panic while locked could break
+        self.port.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl io::Read for ReadHalf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
+    }
+}
+
+impl io::Write for WriteHalf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl fmt::Debug for ReadHalf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReadHalf").finish()
+    }
+}
+
+impl fmt::Debug for WriteHalf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriteHalf").finish()
+    }
+}
+
+impl fmt::Debug for SettingsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SettingsHandle").finish()
+    }
+}
@@ -21,8 +21,8 @@ use winapi::um::winnt::{
 
 use crate::windows::dcb;
 use crate::{
-    ClearBuffer, DataBits, Error, ErrorKind, FlowControl, Parity, Result, SerialPort,
-    SerialPortBuilder, StopBits,
+    ApplyWhen, ClearBuffer, DataBits, Error, ErrorKind, FlowControl, Parity, Result, SerialPort,
+    SerialPortBuilder, SerialSettings, StopBits,
 };
 
 /// A serial port implementation for Windows COM ports
@@ -35,7 +35,8 @@ use crate::{
 pub struct COMPort {
     handle: HANDLE,
     event: HANDLE,
-    timeout: Duration,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
     port_name: Option<String>,
 }
 
@@ -97,12 +98,12 @@ impl COMPort {
         dcb::set_baud_rate(&mut dcb, builder.baud_rate);
         dcb::set_data_bits(&mut dcb, builder.data_bits);
         dcb::set_parity(&mut dcb, builder.parity);
-        dcb::set_stop_bits(&mut dcb, builder.stop_bits);
+        dcb::set_stop_bits(&mut dcb, builder.stop_bits)?;
         dcb::set_flow_control(&mut dcb, builder.flow_control);
         dcb::set_dcb(handle, dcb)?;
 
         let mut com = COMPort::open_from_raw_handle(handle as RawHandle, event);
-        com.set_timeout(builder.timeout)?;
+        com.set_comm_timeouts(builder.read_timeout, builder.write_timeout)?;
         com.port_name = Some(builder.path.clone());
         Ok(com)
     }
@@ -150,12 +151,55 @@ impl COMPort {
             return Ok(COMPort {
                 handle: cloned_handle,
                 port_name: self.port_name.clone(),
-                timeout: self.timeout,
+                read_timeout: self.read_timeout,
+                write_timeout: self.write_timeout,
                 event
             });
         }
     }
 
+    fn set_comm_timeouts(
+        &mut self,
+        read_timeout: Option<Duration>,
+        write_timeout: Option<Duration>,
+    ) -> Result<()> {
+        // Timeouts are specified in milliseconds and must stay below `MAXDWORD`, which has a
+        // special meaning for reads.
+        fn milliseconds(timeout: Duration) -> DWORD {
+            let milliseconds = timeout.as_secs() * 1000 + timeout.subsec_millis() as u64;
+            if milliseconds < 1 {
+                1
+            } else if milliseconds >= MAXDWORD as u64 {
+                MAXDWORD - 1
+            } else {
+                milliseconds as DWORD
+            }
+        }
+
+        let mut timeouts = COMMTIMEOUTS {
+            // Return as soon as any data is available, otherwise wait for the constant timeout.
+            // Waiting indefinitely isn't possible in this mode, so use the largest timeout instead.
+            ReadIntervalTimeout: MAXDWORD,
+            ReadTotalTimeoutMultiplier: MAXDWORD,
+            ReadTotalTimeoutConstant: read_timeout.map_or(MAXDWORD - 1, milliseconds),
+            // A total write timeout of 0 disables write timeouts entirely. A zero write timeout,
+            // which is the builder's default, also waits indefinitely as it always has, rather
+            // than failing any write that doesn't complete within a millisecond.
+            WriteTotalTimeoutMultiplier: 0,
+            WriteTotalTimeoutConstant: match write_timeout {
+                Some(timeout) if timeout > Duration::from_millis(0) => milliseconds(timeout),
+                _ => 0,
+            },
+        };
+
+        if unsafe { SetCommTimeouts(self.handle, &mut timeouts) } == 0 {
+            return Err(super::error::last_os_error());
+        }
+        self.read_timeout = read_timeout;
+        self.write_timeout = write_timeout;
+        Ok(())
+    }
+
     fn escape_comm_function(&mut self, function: DWORD) -> Result<()> {
         match unsafe { EscapeCommFunction(self.handle, function) } {
             0 => Err(super::error::last_os_error()),
@@ -176,7 +220,8 @@ impl COMPort {
         // We'll punt and set it `None` here.
         COMPort {
             handle: handle as HANDLE,
-            timeout: Duration::from_millis(100),
+            read_timeout: Some(Duration::from_millis(100)),
+            write_timeout: Some(Duration::from_millis(100)),
             port_name: None,
             event
         }
@@ -289,42 +334,40 @@ impl SerialPort for COMPort {
         self.port_name.clone()
     }
 
-    fn timeout(&self) -> Duration {
-        self.timeout
+    fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
     }
 
-    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
-        let mut milliseconds = timeout.as_secs() * 1000 + timeout.subsec_millis() as u64;
+    fn write_timeout(&self) -> Option<Duration> {
+        self.write_timeout
+    }
 
-        if milliseconds < 1 {
-            milliseconds = 1;
-        } else if milliseconds >= MAXDWORD as u64 {
-            milliseconds = (MAXDWORD - 1) as u64;
-        }
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.set_comm_timeouts(timeout, self.write_timeout)
+    }
 
-        let mut timeouts = if milliseconds != 0 {
-            COMMTIMEOUTS {
-                ReadIntervalTimeout: MAXDWORD,
-                ReadTotalTimeoutMultiplier: MAXDWORD,
-                ReadTotalTimeoutConstant: milliseconds as DWORD,
-                WriteTotalTimeoutMultiplier: 0,
-                WriteTotalTimeoutConstant: MAXDWORD,
-            }    
-        } else {
-            COMMTIMEOUTS {
-                ReadIntervalTimeout: 0,
-                ReadTotalTimeoutMultiplier: 0,
-                ReadTotalTimeoutConstant: 0,
-                WriteTotalTimeoutMultiplier: 0,
-                WriteTotalTimeoutConstant: 0,
-            } 
-        };
+    fn set_write_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.set_comm_timeouts(self.read_timeout, timeout)
+    }
 
-        if unsafe { SetCommTimeouts(self.handle, &mut timeouts) } == 0 {
-            return Err(super::error::last_os_error());
+    fn apply_settings(&mut self, settings: &SerialSettings, when: ApplyWhen) -> Result<()> {
+        let mut dcb = dcb::get_dcb(self.handle)?;
+        dcb::set_baud_rate(&mut dcb, settings.baud_rate);
+        dcb::set_data_bits(&mut dcb, settings.data_bits);
+        dcb::set_parity(&mut dcb, settings.parity);
+        dcb::set_stop_bits(&mut dcb, settings.stop_bits)?;
+        dcb::set_flow_control(&mut dcb, settings.flow_control);
+
+        match when {
+            ApplyWhen::Now => (),
+            ApplyWhen::Drain => io::Write::flush(self)?,
+            ApplyWhen::Flush => {
+                io::Write::flush(self)?;
+                self.clear(ClearBuffer::Input)?;
+            }
         }
-        self.timeout = timeout;
-        Ok(())
+        dcb::set_dcb(self.handle, dcb)?;
+        self.set_comm_timeouts(settings.read_timeout, settings.write_timeout)
     }
 
     fn write_request_to_send(&mut self, level: bool) -> Result<()> {
@@ -384,6 +427,8 @@ impl SerialPort for COMPort {
             ODDPARITY => Ok(Parity::Odd),
             EVENPARITY => Ok(Parity::Even),
             NOPARITY => Ok(Parity::None),
+            MARKPARITY => Ok(Parity::Mark),
+            SPACEPARITY => Ok(Parity::Space),
             _ => Err(Error::new(
                 ErrorKind::Unknown,
                 "Invalid parity bits setting encountered",
@@ -396,6 +441,7 @@ impl SerialPort for COMPort {
         match dcb.StopBits {
             TWOSTOPBITS => Ok(StopBits::Two),
             ONESTOPBIT => Ok(StopBits::One),
+            ONE5STOPBITS => Ok(StopBits::OnePointFive),
             _ => Err(Error::new(
                 ErrorKind::Unknown,
                 "Invalid stop bits setting encountered",
@@ -421,8 +467,10 @@ impl SerialPort for COMPort {
     }
 
     fn set_data_bits(&mut self, data_bits: DataBits) -> Result<()> {
+        let stop_bits = self.stop_bits()?;
         let mut dcb = dcb::get_dcb(self.handle)?;
         dcb::set_data_bits(&mut dcb, data_bits);
+        dcb::set_stop_bits(&mut dcb, stop_bits)?;
         dcb::set_dcb(self.handle, dcb)
     }
 
@@ -434,7 +482,7 @@ impl SerialPort for COMPort {
 
     fn set_stop_bits(&mut self, stop_bits: StopBits) -> Result<()> {
         let mut dcb = dcb::get_dcb(self.handle)?;
-        dcb::set_stop_bits(&mut dcb, stop_bits);
+        dcb::set_stop_bits(&mut dcb, stop_bits)?;
         dcb::set_dcb(self.handle, dcb)
     }
 
@@ -4,7 +4,7 @@ use winapi::um::commapi::*;
 use winapi::um::winbase::*;
 use winapi::um::winnt::HANDLE;
 
-use crate::{DataBits, FlowControl, Parity, Result, StopBits};
+use crate::{DataBits, Error, ErrorKind, FlowControl, Parity, Result, StopBits};
 
 pub(crate) fn get_dcb(handle: HANDLE) -> Result<DCB> {
     let mut dcb: DCB = unsafe { MaybeUninit::zeroed().assume_init() };
@@ -81,16 +81,32 @@ pub(crate) fn set_parity(dcb: &mut DCB, parity: Parity) {
         Parity::None => NOPARITY as u8,
         Parity::Odd => ODDPARITY as u8,
         Parity::Even => EVENPARITY as u8,
+        Parity::Mark => MARKPARITY as u8,
+        Parity::Space => SPACEPARITY as u8,
     };
 
     dcb.set_fParity(if parity == Parity::None { FALSE } else { TRUE } as DWORD);
 }
 
-pub(crate) fn set_stop_bits(dcb: &mut DCB, stop_bits: StopBits) {
+pub(crate) fn set_stop_bits(dcb: &mut DCB, stop_bits: StopBits) -> Result<()> {
     dcb.StopBits = match stop_bits {
         StopBits::One => ONESTOPBIT as u8,
+        StopBits::OnePointFive if dcb.ByteSize == 5 => ONE5STOPBITS as u8,
+        StopBits::OnePointFive => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "1.5 stop bits require 5 data bits",
+            ))
+        }
+        StopBits::Two if dcb.ByteSize == 5 => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "2 stop bits can't be used with 5 data bits",
+            ))
+        }
         StopBits::Two => TWOSTOPBITS as u8,
     };
+    Ok(())
 }
 
 pub(crate) fn set_flow_control(dcb: &mut DCB, flow_control: FlowControl) {
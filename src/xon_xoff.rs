@@ -0,0 +1,247 @@
+use std::collections::VecDeque;
+use std::io;
+use std::time::Instant;
+
+use crate::{Error, ErrorKind, Result, SerialPort};
+
+/// The size of the buffer each read from the port is made into
+const CHUNK_SIZE: usize = 256;
+
+/// The maximum number of bytes written to the port at once, so that an XOFF is noticed quickly
+const WRITE_CHUNK_SIZE: usize = 64;
+
+/// Implements XON/XOFF software flow control in userspace
+///
+/// Some devices and drivers ignore the kernel's software flow control settings, so that
+/// `FlowControl::Software` has no effect. This wrapper implements it on top of any port instead:
+///
+/// * XON and XOFF characters are removed from the received data.
+/// * Writes wait while the other end has sent XOFF, up to the port's write timeout.
+/// * Received data is buffered, and XOFF is sent once more than the high watermark is buffered.
+///   XON is sent once reads have drained the buffer to the low watermark.
+///
+/// The port's own flow control should be set to `FlowControl::None`. As with any software flow
+/// control, the XON and XOFF characters can't be part of the data.
+///
+/// Data is only received from the port while reading from or writing to the wrapper, so the
+/// watermarks are only checked then.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::io::{Read, Write};
+/// use serialport::XonXoffPort;
+///
+/// let port = serialport::new("/dev/ttyUSB0", 9600).open().unwrap();
+/// let mut port = XonXoffPort::new(port);
+/// port.set_watermarks(512, 128).unwrap();
+///
+/// port.write_all(b"ping").unwrap();
+/// let mut buf = [0u8; 4];
+/// port.read_exact(&mut buf).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct XonXoffPort<P> {
+    port: P,
+    received: VecDeque<u8>,
+    xon: u8,
+    xoff: u8,
+    high_watermark: usize,
+    low_watermark: usize,
+    output_paused: bool,
+    input_paused: bool,
+}
+
+impl<P: SerialPort> XonXoffPort<P> {
+    /// Wraps a port, using the standard DC1/DC3 characters and watermarks of 3072 and 1024 bytes
+    pub fn new(port: P) -> XonXoffPort<P> {
+        XonXoffPort {
+            port,
+            received: VecDeque::new(),
+            xon: 0x11,
+            xoff: 0x13,
+            high_watermark: 3072,
+            low_watermark: 1024,
+            output_paused: false,
+            input_paused: false,
+        }
+    }
+
+    /// Returns the XON and XOFF characters
+    pub fn flow_chars(&self) -> (u8, u8) {
+        (self.xon, self.xoff)
+    }
+
+    /// Sets the XON and XOFF characters
+    pub fn set_flow_chars(&mut self, xon: u8, xoff: u8) {
+        self.xon = xon;
+        self.xoff = xoff;
+    }
+
+    /// Returns the high and low watermarks of the receive buffer
+    pub fn watermarks(&self) -> (usize, usize) {
+        (self.high_watermark, self.low_watermark)
+    }
+
+    /// Sets the number of buffered bytes at which XOFF and XON are sent
+    ///
+    /// ## Errors
+    ///
+    /// * `InvalidInput` if `low` is greater than `high`.
+    pub fn set_watermarks(&mut self, high: usize, low: usize) -> Result<()> {
+        if low > high {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Low watermark is greater than the high watermark",
+            ));
+        }
+        self.high_watermark = high;
+        self.low_watermark = low;
+        Ok(())
+    }
+
+    /// Returns whether writes are paused because the other end sent XOFF
+    pub fn output_paused(&self) -> bool {
+        self.output_paused
+    }
+
+    /// Returns whether XOFF was sent to the other end and not yet followed by XON
+    pub fn input_paused(&self) -> bool {
+        self.input_paused
+    }
+
+    /// Returns the number of received bytes buffered by the wrapper
+    pub fn buffered(&self) -> usize {
+        self.received.len()
+    }
+
+    /// Gets a reference to the underlying port
+    pub fn get_ref(&self) -> &P {
+        &self.port
+    }
+
+    /// Gets a mutable reference to the underlying port
+    ///
+    /// Reading from the port directly bypasses flow control and any buffered data.
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.port
+    }
+
+    /// Returns the underlying port, discarding any buffered data
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// Receives whatever data the port has available without waiting
+    fn receive_available(&mut self) -> io::Result<()> {
+        let mut chunk = [0u8; CHUNK_SIZE];
+        loop {
+            let available = self.port.bytes_to_read()? as usize;
+            if available == 0 {
+                return Ok(());
+            }
+            let n = self.port.read(&mut chunk[..available.min(CHUNK_SIZE)])?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.store(&chunk[..n])?;
+        }
+    }
+
+    /// Reads once from the port, waiting until at most `deadline`
+    ///
+    /// Returns the number of bytes read from the port, which is 0 at end of file.
+    fn receive(&mut self, deadline: Option<Instant>) -> io::Result<usize> {
+        let timeout = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let read_timeout = self.port.read_timeout();
+        self.port.set_read_timeout(timeout)?;
+
+        let mut chunk = [0u8; CHUNK_SIZE];
+        let result = loop {
+            match self.port.read(&mut chunk) {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                result => break result,
+            }
+        };
+
+        self.port.set_read_timeout(read_timeout)?;
+        let n = result?;
+        self.store(&chunk[..n])?;
+        Ok(n)
+    }
+
+    /// Buffers received data, handling any flow control characters in it
+    fn store(&mut self, data: &[u8]) -> io::Result<()> {
+        for &byte in data {
+            if byte == self.xon {
+                self.output_paused = false;
+            } else if byte == self.xoff {
+                self.output_paused = true;
+            } else {
+                self.received.push_back(byte);
+            }
+        }
+        self.update_input_flow()
+    }
+
+    /// Sends XOFF or XON if the receive buffer crossed a watermark
+    fn update_input_flow(&mut self) -> io::Result<()> {
+        if !self.input_paused && self.received.len() >= self.high_watermark {
+            self.port.write_all(&[self.xoff])?;
+            self.input_paused = true;
+        } else if self.input_paused && self.received.len() <= self.low_watermark {
+            self.port.write_all(&[self.xon])?;
+            self.input_paused = false;
+        }
+        Ok(())
+    }
+}
+
+impl<P: SerialPort> io::Read for XonXoffPort<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let deadline = self
+            .port
+            .read_timeout()
+            .map(|timeout| Instant::now() + timeout);
+        self.receive_available()?;
+        // Data may consist of nothing but flow control characters
+        while self.received.is_empty() {
+            if self.receive(deadline)? == 0 {
+                return Ok(0);
+            }
+        }
+
+        let n = buf.len().min(self.received.len());
+        for (dst, src) in buf.iter_mut().zip(self.received.drain(..n)) {
+            *dst = src;
+        }
+        self.update_input_flow()?;
+        Ok(n)
+    }
+}
+
+impl<P: SerialPort> io::Write for XonXoffPort<P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let deadline = self
+            .port
+            .write_timeout()
+            .map(|timeout| Instant::now() + timeout);
+        self.receive_available()?;
+        while self.output_paused {
+            if self.receive(deadline)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Reached end of file while waiting for XON",
+                ));
+            }
+        }
+        self.port.write(&buf[..buf.len().min(WRITE_CHUNK_SIZE)])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
@@ -0,0 +1,31 @@
+//! Tests for the `posix::AsyncTTYPort` struct.
+#![cfg(all(unix, feature = "tokio"))]
+
+use std::time::Duration;
+
+use serialport::{AsyncTTYPort, SerialPort, TTYPort};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[tokio::test]
+async fn test_async_ttyport_pair() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (master, slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    let mut master = AsyncTTYPort::new(master).expect("Unable to register master");
+    let mut slave = AsyncTTYPort::new(slave).expect("Unable to register slave");
+
+    let msg = b"Test Message";
+    master.write_all(msg).await.expect("Unable to write bytes.");
+
+    let mut buf = [0u8; 12];
+    slave
+        .read_exact(&mut buf)
+        .await
+        .expect("Unable to read bytes.");
+    assert_eq!(&buf, msg, "Received message does not match sent");
+
+    // Settings remain accessible through the `SerialPort` trait
+    slave
+        .set_timeout(Duration::from_millis(10))
+        .expect("Unable to set timeout on the slave");
+    assert_eq!(slave.timeout(), Duration::from_millis(10));
+}
@@ -55,3 +55,11 @@ fn test_duplicating_port_config() {
     let _port1 = port1_config.open();
     let _port1 = port2_config.open();
 }
+
+#[test]
+fn test_configuring_separate_timeouts() {
+    let _port = serialport::new("/dev/ttyUSB0", 9600)
+        .read_timeout(None)
+        .write_timeout(Some(Duration::from_millis(1)))
+        .open();
+}
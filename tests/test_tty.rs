@@ -8,7 +8,9 @@ use std::os::unix::prelude::*;
 use std::str;
 use std::time::Duration;
 
-use serialport::{SerialPort, TTYPort};
+use serialport::{
+    FlowControl, LineErrorPolicy, ReceivedByte, SerialPort, SoftwareFlowControl, StopBits, TTYPort,
+};
 
 #[test]
 fn test_ttyport_pair() {
@@ -135,3 +137,772 @@ fn test_ttyport_set_nonstandard_baud() {
     slave.set_baud_rate(1_200_000).unwrap();
     assert_eq!(slave.baud_rate().unwrap(), 1_200_000);
 }
+
+#[test]
+#[cfg(feature = "mio")]
+fn test_ttyport_mio_source() {
+    use mio::{Events, Interest, Poll, Token};
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(4);
+    poll.registry()
+        .register(&mut slave, Token(0), Interest::READABLE)
+        .expect("Unable to register slave");
+
+    // Nothing has been sent yet, so a registered port must not block
+    let mut buf = [0u8; 16];
+    let err = slave.read(&mut buf).expect_err("Read data without sending");
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    let msg = "Test Message";
+    master.write_all(msg.as_bytes()).unwrap();
+
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert!(events
+        .iter()
+        .any(|e| e.token() == Token(0) && e.is_readable()));
+
+    let nbytes = slave.read(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], msg.as_bytes());
+}
+
+#[test]
+fn test_ttyport_nonblocking() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave
+        .set_timeout(Duration::from_millis(10))
+        .expect("Unable to set timeout on the slave");
+
+    slave.set_nonblocking(true).unwrap();
+    assert!(slave.nonblocking());
+
+    let mut buf = [0u8; 16];
+    let err = slave.read(&mut buf).expect_err("Read data without sending");
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    let msg = "Test Message";
+    master.write_all(msg.as_bytes()).unwrap();
+    master.flush().unwrap();
+    let nbytes = slave.read(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], msg.as_bytes());
+
+    // Back in blocking mode the port's timeout applies again
+    slave.set_nonblocking(false).unwrap();
+    assert!(!slave.nonblocking());
+    let err = slave.read(&mut buf).expect_err("Read data without sending");
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn test_ttyport_separate_timeouts() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_read_timeout(None).unwrap();
+    slave
+        .set_write_timeout(Some(Duration::from_millis(10)))
+        .unwrap();
+    assert_eq!(slave.read_timeout(), None);
+    assert_eq!(slave.write_timeout(), Some(Duration::from_millis(10)));
+
+    // With no read timeout the read must wait until data arrives
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        master.write_all(b"x").unwrap();
+        master
+    });
+    let mut buffer = [0u8];
+    assert_eq!(slave.read(&mut buffer).expect("Unable to read bytes."), 1);
+    assert_eq!(buffer[0], b'x');
+    let _master = writer.join().unwrap();
+
+    slave.set_timeout(Duration::from_millis(20)).unwrap();
+    assert_eq!(slave.read_timeout(), Some(Duration::from_millis(20)));
+    assert_eq!(slave.write_timeout(), Some(Duration::from_millis(20)));
+}
+
+#[test]
+fn test_ttyport_restore_indefinite_timeout() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_read_timeout(None).unwrap();
+
+    // Without a read timeout `timeout()` is `Duration::MAX`, which must still be usable
+    let timeout = slave.timeout();
+    assert_eq!(timeout, Duration::MAX);
+    slave.set_timeout(timeout).unwrap();
+
+    master.write_all(b"xy").unwrap();
+    let mut buf = [0u8; 2];
+    slave.read_exact(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf, b"xy");
+    slave.write_all(b"z").expect("Unable to write bytes.");
+    let mut buf = [0u8; 1];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"z");
+}
+
+#[test]
+fn test_ttyport_read_mode() {
+    use serialport::ReadMode;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(slave.read_mode(), ReadMode::Immediate);
+
+    // An inter-byte gap finer than VTIME's resolution is emulated in userspace. The read keeps
+    // collecting bytes that arrive within the gap until `min_bytes` have been received.
+    let mode = ReadMode::Packet {
+        min_bytes: 4,
+        inter_byte_timeout: Duration::from_millis(150),
+    };
+    slave.set_read_mode(mode).unwrap();
+    assert_eq!(slave.read_mode(), mode);
+    let mut clone = master.try_clone_native().unwrap();
+    let writer = std::thread::spawn(move || {
+        clone.write_all(b"ab").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        clone.write_all(b"cdef").unwrap();
+    });
+    let mut buf = [0u8; 16];
+    let nbytes = slave.read(&mut buf).expect("Unable to read bytes.");
+    writer.join().unwrap();
+    assert!(nbytes >= 4, "Read completed before min_bytes arrived");
+    assert_eq!(&buf[..nbytes], &b"abcdef"[..nbytes]);
+    slave.clear(serialport::ClearBuffer::Input).unwrap();
+
+    // A whole number of deciseconds is handled by the kernel through VMIN/VTIME. The line going
+    // idle completes the read before `min_bytes` have arrived.
+    slave
+        .set_read_mode(ReadMode::Packet {
+            min_bytes: 8,
+            inter_byte_timeout: Duration::from_millis(100),
+        })
+        .unwrap();
+    master.write_all(b"xy").unwrap();
+    let nbytes = slave.read(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"xy");
+}
+
+#[test]
+fn test_ttyport_deadline_io() {
+    use std::time::Instant;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_timeout(Duration::from_secs(5)).unwrap();
+    master.set_timeout(Duration::from_secs(5)).unwrap();
+
+    // A partial frame is reported once the deadline passes instead of being discarded
+    master.write_all(b"abc").unwrap();
+    let mut buf = [0u8; 8];
+    let start = Instant::now();
+    let nbytes = slave
+        .read_exact_timeout(&mut buf, start + Duration::from_millis(100))
+        .expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"abc");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(slave.read_timeout(), Some(Duration::from_secs(5)));
+
+    master.write_all(b"defgh").unwrap();
+    let nbytes = slave
+        .read_exact_timeout(&mut buf[..5], Instant::now() + Duration::from_secs(1))
+        .expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"defgh");
+
+    // A write stalls until the deadline passes while output is suspended
+    slave.suspend_output().expect("Unable to suspend output");
+    let start = Instant::now();
+    let nbytes = slave
+        .write_all_timeout(b"ijk", start + Duration::from_millis(100))
+        .expect("Unable to write bytes.");
+    assert_eq!(nbytes, 0);
+    assert!(start.elapsed() >= Duration::from_millis(90));
+    assert_eq!(slave.write_timeout(), Some(Duration::from_secs(5)));
+    slave.resume_output().expect("Unable to resume output");
+}
+
+#[test]
+fn test_ttyport_cancel_handle() {
+    use std::io::ErrorKind;
+    use std::thread;
+    use std::time::Instant;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_read_timeout(None).unwrap();
+    let canceller = slave
+        .cancel_handle()
+        .expect("Unable to create cancel handle");
+    assert!(!canceller.is_cancelled());
+
+    // A read waiting indefinitely is woken up by cancelling from another thread
+    let start = Instant::now();
+    let reader = thread::spawn(move || {
+        let mut buf = [0u8; 8];
+        let err = slave.read(&mut buf).unwrap_err();
+        (slave, err)
+    });
+    thread::sleep(Duration::from_millis(50));
+    canceller.cancel().unwrap();
+    let (mut slave, err) = reader.join().unwrap();
+    assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Cancellation is sticky until reset, even when data is available
+    master.write_all(b"abc").unwrap();
+    let mut buf = [0u8; 8];
+    assert!(canceller.is_cancelled());
+    assert_eq!(
+        slave.read(&mut buf).unwrap_err().kind(),
+        ErrorKind::ConnectionAborted
+    );
+    assert_eq!(
+        slave.write(b"abc").unwrap_err().kind(),
+        ErrorKind::ConnectionAborted
+    );
+
+    canceller.reset().unwrap();
+    assert!(!canceller.is_cancelled());
+    let nbytes = slave.read(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"abc");
+}
+
+#[test]
+fn test_ttyport_port_set() {
+    use serialport::{PortInterest, PortSet};
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master1, mut slave1) = TTYPort::pair().expect("Unable to create ptty pair");
+    let (mut master2, mut slave2) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    let mut set = PortSet::new();
+    let id1 = set.add(&slave1, PortInterest::Readable);
+    let id2 = set.add(&slave2, PortInterest::Readable);
+    assert_eq!(set.len(), 2);
+
+    // Nothing has been sent yet, so the wait times out
+    let events = set.wait(Some(Duration::from_millis(10))).unwrap();
+    assert!(events.is_empty());
+
+    master2.write_all(b"abc").unwrap();
+    let events = set.wait(Some(Duration::from_secs(1))).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].id, id2);
+    assert!(events[0].readable);
+
+    let mut buf = [0u8; 8];
+    let nbytes = slave2.read(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"abc");
+
+    // Both ports report when both have data
+    master1.write_all(b"d").unwrap();
+    master2.write_all(b"e").unwrap();
+    let ids: Vec<usize> = set
+        .wait(Some(Duration::from_secs(1)))
+        .unwrap()
+        .iter()
+        .map(|e| e.id)
+        .collect();
+    assert_eq!(ids, vec![id1, id2]);
+
+    assert!(set.set_interest(id1, PortInterest::Writable));
+    let events = set.wait(None).unwrap();
+    assert!(events.iter().any(|e| e.id == id1 && e.writable));
+
+    assert!(set.remove(id1));
+    assert!(!set.remove(id1));
+    assert_eq!(slave1.read(&mut buf).unwrap(), 1);
+    assert_eq!(slave2.read(&mut buf).unwrap(), 1);
+    let events = set.wait(Some(Duration::from_millis(10))).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_ttyport_serial_reader() {
+    use serialport::{ReadEvent, SerialReader};
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_read_timeout(None).unwrap();
+    let reader = SerialReader::new(Box::new(slave)).expect("Unable to start reader");
+
+    master.write_all(b"Test Message").unwrap();
+    let mut received = Vec::new();
+    while received.len() < 12 {
+        match reader.receiver().recv_timeout(Duration::from_secs(1)) {
+            Ok(ReadEvent::Data(data)) => received.extend(data),
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+    assert_eq!(received, b"Test Message");
+
+    // Stopping returns the port with its original timeout
+    let mut slave = reader.stop().expect("Unable to stop reader");
+    assert_eq!(slave.read_timeout(), None);
+
+    // The same applies to the default timeout of zero
+    slave
+        .set_read_timeout(Some(Duration::from_millis(0)))
+        .unwrap();
+    let reader = SerialReader::new(slave).expect("Unable to start reader");
+    master.write_all(b"Again").unwrap();
+    match reader.receiver().recv_timeout(Duration::from_secs(1)) {
+        Ok(ReadEvent::Data(data)) => assert_eq!(data, b"Again"),
+        event => panic!("Unexpected event {:?}", event),
+    }
+    let slave = reader.stop().expect("Unable to stop reader");
+    assert_eq!(slave.read_timeout(), Some(Duration::from_millis(0)));
+
+    // Hanging up the other end is reported as a disconnect
+    let reader = SerialReader::new(slave).expect("Unable to start reader");
+    drop(master);
+    loop {
+        match reader.receiver().recv_timeout(Duration::from_secs(1)) {
+            Ok(ReadEvent::Disconnected) => break,
+            Ok(ReadEvent::Error(_)) => (),
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+}
+
+#[test]
+fn test_ttyport_read_timestamped() {
+    use std::thread;
+    use std::time::Instant;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+
+    // Two bursts separated by more than the gap are returned separately
+    let start = Instant::now();
+    let writer = thread::spawn(move || {
+        master.write_all(b"abc").unwrap();
+        thread::sleep(Duration::from_millis(200));
+        master.write_all(b"defg").unwrap();
+        master
+    });
+
+    let mut buf = [0u8; 16];
+    let gap = Duration::from_millis(50);
+    let (nbytes, first) = slave
+        .read_timestamped(&mut buf, gap)
+        .expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"abc");
+    let (nbytes, second) = slave
+        .read_timestamped(&mut buf, gap)
+        .expect("Unable to read bytes.");
+    assert_eq!(&buf[..nbytes], b"defg");
+    let _master = writer.join().unwrap();
+
+    assert!(first.instant >= start);
+    assert!(second.instant.duration_since(first.instant) >= Duration::from_millis(150));
+    assert!(second.system_time >= first.system_time);
+}
+
+#[test]
+fn test_ttyport_frame_reader() {
+    use serialport::FrameReader;
+    use std::io::ErrorKind;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    let mut reader = FrameReader::new(Box::new(slave) as Box<dyn SerialPort>);
+    reader.set_frame_timeout(Some(Duration::from_millis(50)));
+
+    // A partial frame is kept across a timeout
+    master.write_all(b"VAL=").unwrap();
+    let err = reader.read_until(b"\r", 64).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(reader.pending(), b"VAL=");
+    master.write_all(b"42\rVAL=").unwrap();
+    assert_eq!(reader.read_until(b"\r", 64).unwrap(), b"VAL=42\r");
+    master.write_all(b"43\r").unwrap();
+    assert_eq!(reader.read_until(b"\r", 64).unwrap(), b"VAL=43\r");
+
+    // Frames without a delimiter are limited to `max_len`
+    master.write_all(b"0123456789\n").unwrap();
+    let err = reader.read_until(b"\n", 4).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.read_until(b"\n", 64).unwrap(), b"456789\n");
+
+    // Any line ending is accepted, including a CRLF split across reads
+    master.write_all(b"first\r").unwrap();
+    assert_eq!(reader.read_line(64).unwrap(), "first");
+    master.write_all(b"\nsecond\nthird\r\n").unwrap();
+    assert_eq!(reader.read_line(64).unwrap(), "second");
+    assert_eq!(reader.read_line(64).unwrap(), "third");
+    assert_eq!(
+        reader.read_line(64).unwrap_err().kind(),
+        ErrorKind::TimedOut
+    );
+
+    // The port's own timeout is left unchanged
+    assert_eq!(
+        reader.get_ref().read_timeout(),
+        Some(Duration::from_millis(100))
+    );
+}
+
+#[test]
+fn test_ttyport_vectored_io() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    // The whole frame is handed to the kernel in a single write
+    let frame = [
+        IoSlice::new(b"\x02"),
+        IoSlice::new(b"payload"),
+        IoSlice::new(b"\x1f\x03"),
+    ];
+    let nbytes = master
+        .write_vectored(&frame)
+        .expect("Unable to write bytes.");
+    assert_eq!(nbytes, 10);
+
+    let mut header = [0u8; 1];
+    let mut rest = [0u8; 16];
+    let mut bufs = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut rest)];
+    let nbytes = slave
+        .read_vectored(&mut bufs)
+        .expect("Unable to read bytes.");
+    assert_eq!(nbytes, 10);
+    assert_eq!(&header, b"\x02");
+    assert_eq!(&rest[..9], b"payload\x1f\x03");
+
+    // Timeouts apply the same as for `read()`
+    let mut bufs = [IoSliceMut::new(&mut header)];
+    let err = slave.read_vectored(&mut bufs).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn test_ttyport_shared_reference_io() {
+    use std::sync::Arc;
+    use std::thread;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_timeout(Duration::from_secs(1)).unwrap();
+    let master = Arc::new(master);
+
+    // One thread writes while another reads through the same shared port
+    let writer = {
+        let master = master.clone();
+        thread::spawn(move || (&*master).write_all(b"ping").unwrap())
+    };
+    let mut buf = [0u8; 4];
+    slave.read_exact(&mut buf).expect("Unable to read bytes.");
+    assert_eq!(&buf, b"ping");
+    writer.join().unwrap();
+
+    let reader = {
+        let master = master.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 4];
+            (&*master).read_exact(&mut buf).unwrap();
+            buf
+        })
+    };
+    slave.write_all(b"pong").unwrap();
+    assert_eq!(&reader.join().unwrap(), b"pong");
+}
+
+#[test]
+fn test_ttyport_split() {
+    use std::io::ErrorKind;
+    use std::thread;
+    use std::time::Instant;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    let (mut reader, mut writer, settings) = slave.split();
+
+    // Timeout changes through the settings handle apply to the halves
+    settings
+        .lock()
+        .set_read_timeout(Some(Duration::from_millis(10)))
+        .unwrap();
+    let mut buf = [0u8; 8];
+    let start = Instant::now();
+    assert_eq!(
+        reader.read(&mut buf).unwrap_err().kind(),
+        ErrorKind::TimedOut
+    );
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    // The reader doesn't hold up the writer while waiting for data
+    settings
+        .lock()
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let read_thread = thread::spawn(move || {
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf).unwrap();
+        (reader, buf)
+    });
+    writer.write_all(b"ping").unwrap();
+    let mut buf = [0u8; 4];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+    master.write_all(b"pong").unwrap();
+    let (reader, buf) = read_thread.join().unwrap();
+    assert_eq!(&buf, b"pong");
+
+    // The port stays open until all of the handles are dropped
+    drop(reader);
+    drop(writer);
+    assert_eq!(settings.lock().bytes_to_read().unwrap(), 0);
+    drop(settings);
+    let mut buf = [0u8; 1];
+    assert!(master.read(&mut buf).is_err());
+}
+
+#[test]
+fn test_ttyport_split_boxed() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    let slave: Box<dyn SerialPort> = Box::new(slave);
+    let (mut reader, _writer, settings) = slave.split().expect("Unable to split port");
+    settings.lock().set_timeout(Duration::from_secs(1)).unwrap();
+
+    master.write_all(b"abc").unwrap();
+    let mut buf = [0u8; 3];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abc");
+}
+
+#[test]
+fn test_ttyport_flush_timeout() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    // A pty hands written data straight to the other end, so there's never anything queued
+    master.write_all(b"abc").unwrap();
+    master
+        .flush_timeout(Duration::from_millis(100))
+        .expect("Unable to flush");
+    master.flush().expect("Unable to flush");
+
+    let mut buf = [0u8; 3];
+    slave.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abc");
+}
+
+#[test]
+fn test_ttyport_wait_tx_complete() {
+    use std::thread;
+    use std::time::Instant;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave.set_baud_rate(300).unwrap();
+
+    // Nothing has been written yet
+    assert!(slave.tx_empty().unwrap());
+
+    // ptys don't report the transmitter status, so the output only counts as transmitted once one
+    // character time, 33ms at 300 baud 8N1, has passed since the last write
+    slave.write_all(b"abc").unwrap();
+    assert!(!slave.tx_empty().unwrap());
+    thread::sleep(Duration::from_millis(40));
+    assert!(slave.tx_empty().unwrap());
+    slave.write_all(b"def").unwrap();
+    assert!(!slave.tx_empty().unwrap());
+
+    let start = Instant::now();
+    slave
+        .wait_tx_complete(Duration::from_secs(1))
+        .expect("Unable to wait for transmission");
+    assert!(start.elapsed() >= Duration::from_millis(33));
+
+    // The character time is cut short by the timeout
+    slave.write_all(b"ghi").unwrap();
+    let start = Instant::now();
+    slave
+        .wait_tx_complete(Duration::from_millis(5))
+        .expect("Unable to wait for transmission");
+    assert!(start.elapsed() < Duration::from_millis(30));
+
+    let mut buf = [0u8; 9];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abcdefghi");
+}
+
+#[test]
+fn test_ttyport_one_point_five_stop_bits() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (_master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    // ptys always use 8 data bits, which can't be combined with 1.5 stop bits
+    let err = slave.set_stop_bits(StopBits::OnePointFive).unwrap_err();
+    assert_eq!(err.kind(), serialport::ErrorKind::InvalidInput);
+    assert_eq!(slave.stop_bits().unwrap(), StopBits::One);
+}
+
+#[test]
+fn test_ttyport_read_with_errors() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave
+        .set_line_error_policy(Some(LineErrorPolicy::Report))
+        .expect("Unable to set the line error policy");
+    assert_eq!(slave.line_error_policy(), Some(LineErrorPolicy::Report));
+
+    // A valid `\377` is escaped by the kernel and must come out as a single byte
+    master.write_all(&[0x41, 0xFF, 0x42]).unwrap();
+
+    let mut received = Vec::new();
+    let mut buf = [ReceivedByte::default(); 8];
+    while received.len() < 3 {
+        let n = slave.read_with_errors(&mut buf).expect("Unable to read");
+        received.extend_from_slice(&buf[..n]);
+    }
+    let values: Vec<u8> = received.iter().map(|b| b.value).collect();
+    assert_eq!(values, [0x41, 0xFF, 0x42]);
+    assert!(received.iter().all(|b| b.error.is_none()));
+}
+
+#[test]
+fn test_ttyport_software_flow_control() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (_master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    let settings = SoftwareFlowControl {
+        xon: b'Q',
+        xoff: b'S',
+        xany: true,
+        input: false,
+        output: true,
+    };
+    slave
+        .set_software_flow_control(settings)
+        .expect("Unable to set software flow control");
+    assert_eq!(slave.software_flow_control().unwrap(), settings);
+
+    slave
+        .set_software_flow_control(SoftwareFlowControl::default())
+        .unwrap();
+    assert_eq!(slave.flow_control().unwrap(), FlowControl::Software);
+}
+
+#[test]
+fn test_ttyport_manual_flow_control() {
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    slave.send_xoff().expect("Unable to send XOFF");
+    slave.send_xon().expect("Unable to send XON");
+    let mut buf = [0u8; 2];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x13, 0x11]);
+
+    // Writes can't complete while output is suspended
+    slave.suspend_output().expect("Unable to suspend output");
+    assert!(slave.output_suspended());
+    slave.set_nonblocking(true).unwrap();
+    let err = slave.write(b"abc").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock);
+
+    slave.resume_output().expect("Unable to resume output");
+    assert!(!slave.output_suspended());
+    slave.write_all(b"abc").unwrap();
+    let mut buf = [0u8; 3];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abc");
+}
+
+#[test]
+fn test_xon_xoff_port() {
+    use serialport::XonXoffPort;
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+    slave
+        .set_write_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    let mut port = XonXoffPort::new(slave);
+    port.set_watermarks(4, 2).unwrap();
+
+    // Flow control characters are removed from the data and pause writes
+    master.write_all(&[b'a', 0x13, b'b']).unwrap();
+    let mut buf = [0u8; 2];
+    port.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ab");
+    assert!(port.output_paused());
+    let err = port.write(b"abc").unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+    master.write_all(&[0x11]).unwrap();
+    port.write_all(b"abc").unwrap();
+    let mut buf = [0u8; 3];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abc");
+
+    // Buffering more than the high watermark sends XOFF, draining to the low watermark sends XON
+    master.write_all(b"123456").unwrap();
+    // Give the pty time to deliver all of the data before the wrapper receives it
+    std::thread::sleep(Duration::from_millis(50));
+    let mut buf = [0u8; 1];
+    port.read_exact(&mut buf).unwrap();
+    assert!(port.input_paused());
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x13]);
+
+    let mut buf = [0u8; 3];
+    port.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"234");
+    assert!(!port.input_paused());
+    let mut buf = [0u8; 1];
+    master.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x11]);
+}
+
+#[test]
+fn test_ttyport_apply_settings() {
+    use serialport::{ApplyWhen, SerialSettings};
+
+    // FIXME: Create a mutex across all tests for using `TTYPort::pair()` as it's not threadsafe
+    let (mut master, mut slave) = TTYPort::pair().expect("Unable to create ptty pair");
+
+    // ptys always use 8 data bits without parity, so only the other settings can change
+    let settings = SerialSettings {
+        baud_rate: 115_200,
+        stop_bits: StopBits::Two,
+        flow_control: FlowControl::Software,
+        read_timeout: Some(Duration::from_millis(250)),
+        write_timeout: None,
+        ..slave.settings().expect("Unable to read settings")
+    };
+    slave
+        .apply_settings(&settings, ApplyWhen::Drain)
+        .expect("Unable to apply settings");
+    assert_eq!(slave.settings().unwrap(), settings);
+
+    // Input that hasn't been read is discarded when flushing
+    master.write_all(b"abc").unwrap();
+    while slave.bytes_to_read().unwrap() < 3 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let settings = SerialSettings {
+        baud_rate: 9600,
+        stop_bits: StopBits::One,
+        flow_control: FlowControl::None,
+        ..settings
+    };
+    slave
+        .apply_settings(&settings, ApplyWhen::Flush)
+        .expect("Unable to apply settings");
+    assert_eq!(slave.settings().unwrap(), settings);
+    assert_eq!(slave.bytes_to_read().unwrap(), 0);
+}